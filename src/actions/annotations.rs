@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+
+use crate::structs::{AnnotationKind, BuildInfo, ComponentStructure, Info, Side, TikzComponent};
+use crate::{GRID_SIZE, TEXT_SCALE};
+
+/// Text, arrow or polarity sign drawn for an annotation. Children of the bipole, so they follow it.
+#[derive(Component)]
+pub struct AnnotationMark;
+
+/// Length of the body of a bipole, the part drawn by its meshes.
+const BODY: f32 = GRID_SIZE * 1.5;
+const COLOR: Color = Color::srgb(0.55, 0.75, 1.0);
+
+fn text(content: &str, pos: Vec2) -> impl Bundle {
+    (
+        Text2d::new(content),
+        TextColor(COLOR),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Transform::from_translation(pos.extend(1.0)).with_scale(Vec3::splat(TEXT_SCALE)),
+        AnnotationMark,
+    )
+}
+
+fn segment(from: Vec2, to: Vec2) -> impl Bundle {
+    let delta = to - from;
+    (
+        Sprite::from_color(COLOR, Vec2::ONE),
+        Transform::from_translation(from.midpoint(to).extend(1.0))
+            .with_rotation(Quat::from_rotation_z(delta.to_angle()))
+            .with_scale(Vec3::new(delta.length(), 0.5, 1.0)),
+        AnnotationMark,
+    )
+}
+
+/// Arrow of `length` centered on `center`, pointing to +x, or -x when `reversed`.
+fn arrow(p: &mut ChildBuilder, center: Vec2, length: f32, reversed: bool) {
+    let direction = if reversed { -1. } else { 1. };
+    let tip = center + Vec2::X * direction * length / 2.;
+    p.spawn(segment(center - Vec2::X * direction * length / 2., tip));
+    for side in [-1., 1.] {
+        p.spawn(segment(tip, tip + Vec2::new(-direction * 3., side * 2.)));
+    }
+}
+
+/// Draws the annotations of every bipole whose [`Info`] or size changed, replacing the old marks.
+/// Wires have no [`BuildInfo`], so their length comes from their ends.
+pub fn draw_annotations(
+    mut commands: Commands,
+    components: Query<
+        (
+            Entity,
+            &TikzComponent,
+            &Info,
+            Option<&BuildInfo>,
+            &ComponentStructure,
+            Option<&Children>,
+        ),
+        Or<(Changed<Info>, Changed<BuildInfo>, Changed<ComponentStructure>)>,
+    >,
+    marks: Query<(), With<AnnotationMark>>,
+) {
+    for (entity, cc, info, build_info, structure, children) in &components {
+        for &child in children.into_iter().flatten() {
+            if marks.contains(child) {
+                commands.entity(child).despawn_recursive();
+            }
+        }
+        // Circuitikz only annotates bipoles.
+        if cc.is_single() || info.annotations.is_empty() {
+            continue;
+        }
+
+        let len = match (build_info, structure) {
+            (Some(build_info), _) => build_info.len,
+            (None, ComponentStructure::To([initial, fin])) => initial.distance(*fin),
+            (None, ComponentStructure::Node(_)) => continue,
+        };
+        let half = len / 2.;
+        // Middle of the wires at each side of the body.
+        let lead = (half + BODY / 2.) / 2.;
+        commands.entity(entity).with_children(|p| {
+            for annotation in &info.annotations {
+                let side = match annotation.side() {
+                    Side::Above => 1.,
+                    Side::Below => -1.,
+                };
+                let text_pos = Vec2::new(0., side * GRID_SIZE * 1.2);
+                match annotation.kind {
+                    AnnotationKind::Label | AnnotationKind::Annotation => {
+                        p.spawn(text(&annotation.text, text_pos));
+                    }
+                    AnnotationKind::Voltage => {
+                        let (start, end) = if annotation.reversed() { ("-", "+") } else { ("+", "-") };
+                        let y = side * GRID_SIZE * 0.8;
+                        p.spawn(text(start, Vec2::new(-BODY / 2., y)));
+                        p.spawn(text(end, Vec2::new(BODY / 2., y)));
+                        p.spawn(text(&annotation.text, text_pos));
+                    }
+                    AnnotationKind::Current => {
+                        let x = if annotation.at_start() { -lead } else { lead };
+                        arrow(p, Vec2::new(x, 0.), 8., annotation.reversed());
+                        p.spawn(text(&annotation.text, Vec2::new(x, side * GRID_SIZE * 0.8)));
+                    }
+                    AnnotationKind::Flow => {
+                        let y = side * GRID_SIZE * 0.8;
+                        arrow(p, Vec2::new(0., y), BODY, annotation.reversed());
+                        p.spawn(text(&annotation.text, Vec2::new(0., side * GRID_SIZE * 1.5)));
+                    }
+                }
+            }
+        });
+    }
+}
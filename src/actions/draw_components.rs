@@ -3,6 +3,8 @@ use bevy::prelude::*;
 
 use crate::components::Handles;
 use crate::graph::AddToGraph;
+use crate::history::Checkpoint;
+use crate::project::{records, RecordQuery};
 use crate::{actions, structs::*, TEXT_SCALE};
 
 use crate::GRID_SIZE;
@@ -45,32 +47,16 @@ pub struct InitiateComponent {
 pub fn draw_initial_component(
     trigger: Trigger<InitiateComponent>, mut commands: Commands,
     dots: Query<(Entity, &GlobalTransform), With<FirstPos>>, cc: Res<TikzComponent>,
-    handles: Res<Handles>, material: ResMut<Assets<ColorMaterial>>,
+    handles: Res<Handles>, mut material: ResMut<Assets<ColorMaterial>>,
 ) {
     let InitiateComponent { pos } = trigger.event();
     let pos = pos.extend(0.);
 
-    let text_height = cc.get_label_height();
-    let initial = pos;
-    let structure = ComponentStructure::Node(initial.truncate());
-    match *cc {
-        TikzComponent::Dot => {
-            let dot = commands
-                .spawn(initial_component(*cc, structure, initial, 0.0))
-                .with_children(|p| {
-                    p.spawn(label(text_height));
-
-                    p.spawn(Sprite::from_color(Color::Srgba(Srgba::gray(0.5)), Vec2::splat(4.0)));
-                })
-                .id();
-            commands.trigger(AddToGraph(structure, dot));
-            return;
-        }
-        x if x.is_single() => {
-            draw_from_mesh(&mut commands, *cc, handles, material, structure);
-            return;
-        }
-        _ => {}
+    if cc.is_single() {
+        let structure = ComponentStructure::Node(pos.truncate());
+        commands.trigger(Checkpoint);
+        spawn_component(&mut commands, *cc, &handles, &mut material, structure);
+        return;
     }
 
     let Ok((dot_ent, dot_transform)) = dots.get_single() else {
@@ -93,37 +79,117 @@ pub fn draw_initial_component(
         return;
     }
 
-    let initial = dot_translation;
-    let fin = pos;
-    let middle = (initial + fin) / 2.0;
-    let len = (fin - initial).length();
-    let angle = (fin.y - initial.y).atan2(fin.x - initial.x);
-    let structure = ComponentStructure::To([initial.truncate(), fin.truncate()]);
-    if *cc == TikzComponent::Line {
-        let line = commands
-            .spawn(initial_component(*cc, structure, middle, angle))
-            .with_children(|p| {
-                p.spawn(label(text_height));
+    let structure = ComponentStructure::To([dot_translation.truncate(), pos.truncate()]);
+    commands.trigger(Checkpoint);
+    spawn_component(&mut commands, *cc, &handles, &mut material, structure);
+}
 
-                p.spawn((
-                    Sprite {
-                        color: Color::WHITE,
-                        ..default()
-                    },
-                    Transform::from_scale(Vec3::new(len, 0.5, 1.0)),
-                ));
-            })
-            .id();
-        commands.trigger(AddToGraph(structure, line));
+/// First end of a wire being routed, waiting for the click on the other end.
+#[derive(Component)]
+pub struct RouteStart;
+
+#[derive(Event)]
+pub struct RouteWire {
+    pub pos: Vec2,
+}
+
+/// The first click marks where the wire starts and the second draws it, as wires around the
+/// components. Clicks close to a pin or a wire end start and end on it.
+pub fn route_wire(
+    trigger: Trigger<RouteWire>, mut commands: Commands,
+    starts: Query<(Entity, &Transform), With<RouteStart>>, components: RecordQuery,
+    handles: Res<Handles>, mut material: ResMut<Assets<ColorMaterial>>,
+) {
+    let records = records(&components);
+    let pos = trigger.event().pos;
+    let pos = records
+        .iter()
+        .flat_map(|record| record.terminals())
+        .filter(|terminal| terminal.distance(pos) < GRID_SIZE / 2.)
+        .min_by(|a, b| a.distance(pos).total_cmp(&b.distance(pos)))
+        .unwrap_or(pos);
+
+    let Ok((start_ent, start_transform)) = starts.get_single() else {
+        commands.spawn((
+            Sprite::default(),
+            Transform::from_translation(pos.extend(0.)).with_scale(Vec3::splat(2.0)),
+            RouteStart,
+        ));
         return;
+    };
+    let start = start_transform.translation.truncate();
+    commands.entity(start_ent).despawn();
+
+    let Some(points) = crate::graph::routing::route(&records, start, pos) else {
+        warn!("No route from {start} to {pos}");
+        return;
+    };
+    commands.trigger(Checkpoint);
+    for pair in points.windows(2) {
+        let structure = ComponentStructure::To([pair[0], pair[1]]);
+        spawn_component(&mut commands, TikzComponent::Line, &handles, &mut material, structure);
+    }
+}
+
+pub fn clear_route_start(mut commands: Commands, starts: Query<Entity, With<RouteStart>>) {
+    for start in &starts {
+        commands.entity(start).despawn();
+    }
+}
+
+/// Spawns any kind of component with the given structure and adds it to the graph.
+pub fn spawn_component(
+    commands: &mut Commands, cc: TikzComponent, handles: &Handles,
+    material: &mut Assets<ColorMaterial>, structure: ComponentStructure,
+) -> Entity {
+    let text_height = cc.get_label_height();
+    match (cc, structure) {
+        (TikzComponent::Dot, _) => {
+            let initial = match structure {
+                ComponentStructure::Node(pos) => pos,
+                ComponentStructure::To([pos, _]) => pos,
+            };
+            let structure = ComponentStructure::Node(initial);
+            let dot = commands
+                .spawn(initial_component(cc, structure, initial.extend(0.), 0.0))
+                .with_children(|p| {
+                    p.spawn(label(text_height));
+
+                    p.spawn(Sprite::from_color(Color::Srgba(Srgba::gray(0.5)), Vec2::splat(4.0)));
+                })
+                .id();
+            commands.trigger(AddToGraph(structure, dot));
+            dot
+        }
+        (TikzComponent::Line, ComponentStructure::To([initial, fin])) => {
+            let middle = (initial + fin) / 2.0;
+            let len = (fin - initial).length();
+            let angle = (fin.y - initial.y).atan2(fin.x - initial.x);
+            let line = commands
+                .spawn(initial_component(cc, structure, middle.extend(0.), angle))
+                .with_children(|p| {
+                    p.spawn(label(text_height));
+
+                    p.spawn((
+                        Sprite {
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                        Transform::from_scale(Vec3::new(len, 0.5, 1.0)),
+                    ));
+                })
+                .id();
+            commands.trigger(AddToGraph(structure, line));
+            line
+        }
+        _ => draw_from_mesh(commands, cc, handles, material, structure),
     }
-    draw_from_mesh(&mut commands, *cc, handles, material, structure);
 }
 
 pub fn draw_from_mesh(
-    commands: &mut Commands, cc: TikzComponent, handles: Res<Handles>,
-    mut material: ResMut<Assets<ColorMaterial>>, structure: ComponentStructure,
-) {
+    commands: &mut Commands, cc: TikzComponent, handles: &Handles,
+    material: &mut Assets<ColorMaterial>, structure: ComponentStructure,
+) -> Entity {
     const SIZE: f32 = GRID_SIZE * 1.5;
     let (initial, len, angle, middle) = match structure {
         ComponentStructure::To([initial, fin]) => {
@@ -174,97 +240,25 @@ pub fn draw_from_mesh(
             }
         })
         .id();
-    if cc.is_gate() {
-        fill_gate_labels(component, commands, cc);
-    }
-
-    if cc == TikzComponent::AmpOp {
-        fill_amp_labels(component, commands);
-    }
+    fill_pin_labels(component, commands, cc);
     commands.trigger(AddToGraph(structure, component));
+    component
 }
 
-fn fill_amp_labels(amp: Entity, commands: &mut Commands) {
-    let minus_in = commands
-        .spawn((
-            Transform::from_xyz(-2.0 * GRID_SIZE, GRID_SIZE, 0.0),
-            ComponentLabel {
-                label: ".-".to_string(),
-            },
-        ))
-        .id();
-    let plus_in = commands
-        .spawn((
-            Transform::from_xyz(-2.0 * GRID_SIZE, -GRID_SIZE, 0.0),
-            ComponentLabel {
-                label: ".+".to_string(),
-            },
-        ))
-        .id();
-    let out = commands
-        .spawn((
-            Transform::from_xyz(3.0 * GRID_SIZE, 0., 0.0),
-            ComponentLabel {
-                label: ".out".to_string(),
-            },
-        ))
-        .id();
-    commands.entity(amp).add_children(&[minus_in, plus_in, out]);
-}
-fn fill_gate_labels(gate: Entity, commands: &mut Commands, comp_type: TikzComponent) {
-    use TikzComponent::*;
-    match comp_type {
-        AndGate | OrGate | XorGate => {
-            let in1 = commands
-                .spawn((
-                    Transform::from_xyz(-2.0 * GRID_SIZE, GRID_SIZE, 1.0),
-                    ComponentLabel {
-                        label: ".in 1".to_string(),
-                    },
-                ))
-                .id();
-
-            let in2 = commands
-                .spawn((
-                    Transform::from_xyz(-2.0 * GRID_SIZE, -GRID_SIZE, 1.0),
-                    ComponentLabel {
-                        label: ".in 2".to_string(),
-                    },
-                ))
-                .id();
-
-            let out = commands
+fn fill_pin_labels(component: Entity, commands: &mut Commands, comp_type: TikzComponent) {
+    let pins: Vec<_> = comp_type
+        .pins()
+        .iter()
+        .map(|(label, offset)| {
+            commands
                 .spawn((
-                    Transform::from_xyz(2.0 * GRID_SIZE, 0.0, 1.0),
+                    Transform::from_translation((*offset * GRID_SIZE).extend(1.0)),
                     ComponentLabel {
-                        label: ".out".to_string(),
+                        label: label.to_string(),
                     },
                 ))
-                .id();
-
-            commands.entity(gate).add_children(&[in1, in2, out]);
-        }
-
-        NotGate => {
-            let in1 = commands
-                .spawn((
-                    Transform::from_xyz(-1.0 * GRID_SIZE, -GRID_SIZE, 1.0),
-                    ComponentLabel {
-                        label: ".in".to_string(),
-                    },
-                ))
-                .id();
-
-            let out = commands
-                .spawn((
-                    Transform::from_xyz(1.0 * GRID_SIZE, 0.0, 1.0),
-                    ComponentLabel {
-                        label: ".out".to_string(),
-                    },
-                ))
-                .id();
-            commands.entity(gate).add_children(&[in1, out]);
-        }
-        _ => unreachable!("Only gate type"),
-    }
+                .id()
+        })
+        .collect();
+    commands.entity(component).add_children(&pins);
 }
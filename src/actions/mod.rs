@@ -1,19 +1,91 @@
-use crate::graph::RemoveFromGraph;
-use crate::structs::{Anchored, ComponentLabel, CursorPosition, TikzComponent};
+use crate::graph::{RemoveFromGraph, UpdateChildLabels};
+use crate::history::Checkpoint;
+use crate::structs::{Anchored, BuildInfo, ComponentLabel, CursorPosition, TikzComponent};
 use bevy::prelude::*;
 use bevy::utils::HashMap;
 
+pub mod annotations;
 pub mod draw_components;
 pub mod select_node;
 
-#[allow(clippy::type_complexity)]
+/// Cursor position and anchors of the selected components when the move started.
+#[derive(Resource, Default)]
+pub struct MoveStart {
+    cursor: Vec2,
+    anchors: HashMap<Entity, Vec2>,
+}
+
+pub fn start_move(
+    mut move_start: ResMut<MoveStart>, cursor_positon: Res<CursorPosition>,
+    components: Query<(Entity, &Anchored), With<select_node::Selected>>,
+) {
+    *move_start = MoveStart {
+        cursor: cursor_positon.pos,
+        anchors: components
+            .iter()
+            .map(|(entity, anchor)| (entity, anchor.0))
+            .collect(),
+    };
+}
+
+/// Moves every selected component by how much the cursor moved, keeping the group shape.
 pub fn move_entity(
-    mut commands: Commands, cursor_positon: Res<CursorPosition>,
-    component: Single<Entity, (With<select_node::Selected>, With<Anchored>)>,
+    mut commands: Commands, cursor_positon: Res<CursorPosition>, move_start: Res<MoveStart>,
+    selected: Query<Entity, (With<select_node::Selected>, With<Anchored>)>,
 ) {
-    commands
-        .entity(*component)
-        .insert(Anchored(cursor_positon.pos));
+    let delta = cursor_positon.pos - move_start.cursor;
+    for (&entity, &anchor) in &move_start.anchors {
+        if selected.contains(entity) {
+            commands.entity(entity).insert(Anchored(anchor + delta));
+        }
+    }
+}
+
+/// Turns or mirrors every selected node around its own center.
+#[derive(Event, Clone, Copy)]
+pub enum OrientSelected {
+    Rotate,
+    Mirror { vertical: bool },
+}
+
+pub fn orient_selected(
+    trigger: Trigger<OrientSelected>, mut commands: Commands,
+    selected: Query<(Entity, &TikzComponent, &BuildInfo, &Anchored), With<select_node::Selected>>,
+) {
+    let nodes: Vec<_> = selected
+        .iter()
+        .filter(|(_, cc, ..)| cc.is_single())
+        .collect();
+    if nodes.is_empty() {
+        return;
+    }
+
+    commands.trigger(Checkpoint);
+    for (entity, _, build_info, anchor) in nodes {
+        let build_info = match *trigger.event() {
+            OrientSelected::Rotate => build_info.rotated(),
+            OrientSelected::Mirror { vertical } => build_info.mirrored(vertical),
+        };
+        // The anchor hook applies the new orientation to the transform.
+        commands.entity(entity).insert((build_info, Anchored(anchor.0)));
+    }
+    commands.trigger(UpdateChildLabels);
+}
+
+pub fn orientation_shortcuts(
+    mut commands: Commands, keys: Res<ButtonInput<KeyCode>>,
+    focused: Res<crate::ui::FocusedInputText>,
+) {
+    if focused.0 != Entity::PLACEHOLDER || !select_node::ctrl_pressed(&keys) {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyR) {
+        commands.trigger(OrientSelected::Rotate);
+    } else if keys.just_pressed(KeyCode::KeyF) {
+        let vertical = select_node::shift_pressed(&keys);
+        commands.trigger(OrientSelected::Mirror { vertical });
+    }
 }
 
 #[derive(Event)]
@@ -27,27 +99,22 @@ pub fn delete_component(trigger: Trigger<DeleteComponent>, mut commands: Command
 #[derive(Event)]
 pub struct UpdateComponentLabel;
 
+/// Numbers the components that have no label yet. The others keep theirs, so the saved labels
+/// of a project are not lost on the next edit.
 pub fn update_component_label(
-    _: Trigger<UpdateComponentLabel>, mut components: Query<(&mut ComponentLabel, &TikzComponent)>,
+    _: Trigger<UpdateComponentLabel>,
+    mut components: Query<(Entity, &mut ComponentLabel, &TikzComponent)>,
 ) {
-    let mut map = HashMap::<String, u32>::new();
-    for (mut label, typec) in components.iter_mut() {
-        let f_type = match typec {
-            TikzComponent::AndGate => "AND",
-            TikzComponent::OrGate => "OR",
-            TikzComponent::XorGate => "XOR",
-            TikzComponent::NotGate => "NOT",
-            TikzComponent::AmpOp => "AOP",
-            TikzComponent::Transformer => "T",
-            TikzComponent::Transistor => "S",
-            _ => "E",
-        };
-
-        let count = map.entry(f_type.to_string()).or_insert(0);
-        *count += 1;
-
-        *label = ComponentLabel {
-            label: format!("{f_type}{count}"),
-        };
+    let mut components: Vec<_> = components.iter_mut().collect();
+    components.sort_by_key(|(entity, ..)| *entity);
+    let labels = ComponentLabel::numbered(
+        components
+            .iter()
+            .map(|(_, label, typec)| (**typec, label.label.as_str())),
+    );
+    for ((_, label, _), new_label) in components.iter_mut().zip(labels) {
+        if label.label != new_label.label {
+            **label = new_label;
+        }
     }
 }
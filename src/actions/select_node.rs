@@ -1,4 +1,5 @@
 use crate::actions::DeleteComponent;
+use crate::history::Checkpoint;
 use bevy::prelude::*;
 
 use super::draw_components::ActualComponent;
@@ -9,6 +10,19 @@ pub struct Selectable;
 #[derive(Component)]
 pub struct Selected;
 
+/// How much bigger a selected component is drawn.
+pub const HIGHLIGHT: f32 = 1.5;
+
+#[inline]
+pub fn shift_pressed(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+#[inline]
+pub fn ctrl_pressed(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+}
+
 pub fn on_add_selected(
     trigger: Trigger<OnAdd, Selected>,
     mut query: Query<(&Parent, &mut Transform), (With<Parent>, With<ActualComponent>)>,
@@ -16,7 +30,7 @@ pub fn on_add_selected(
     let entity = trigger.entity();
     for (parent, mut transform) in query.iter_mut() {
         if parent.get() == entity {
-            transform.scale *= 1.5;
+            transform.scale *= HIGHLIGHT;
         }
     }
 }
@@ -28,10 +42,24 @@ pub fn on_remove_selected(
     let entity = trigger.entity();
     for (parent, mut transform) in query.iter_mut() {
         if parent.get() == entity {
-            transform.scale /= 1.5;
+            transform.scale /= HIGHLIGHT;
         }
     }
 }
-pub fn despawn_selected(mut commands: Commands, selected: Single<Entity, With<Selected>>) {
-    commands.trigger_targets(DeleteComponent, *selected);
+pub fn despawn_selected(mut commands: Commands, selected: Query<Entity, With<Selected>>) {
+    if selected.is_empty() {
+        return;
+    }
+    commands.trigger(Checkpoint);
+    commands.trigger_targets(DeleteComponent, selected.iter().collect::<Vec<_>>());
+}
+
+/// Rubber band drawn while dragging on the grid, holding where the drag started.
+#[derive(Component)]
+pub struct SelectionBox(pub Vec2);
+
+impl SelectionBox {
+    pub fn rect(&self, cursor: Vec2) -> Rect {
+        Rect::from_corners(self.0, cursor)
+    }
 }
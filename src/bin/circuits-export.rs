@@ -0,0 +1,152 @@
+//! Converts a saved circuit project to TikZ without opening a window.
+//!
+//! Usage: `circuits-export [--symbols <library.ron>] <input> [output]`, where the input is a
+//! `.ron` project or a `.cir`/`.sp` deck and the output a `.tex`, `.cir`, `.sp` or `.ron` file.
+//! The TikZ is wrapped as the export profile of the project asks and, without an output file,
+//! written to stdout. Outputs ending in `.cir` or `.sp` get a SPICE deck instead, and `.ron`
+//! outputs the project itself. Inputs ending in `.cir` or `.sp` are SPICE decks placed on the grid.
+//! Symbols of the library are read from the file given with `--symbols`, or from
+//! `assets/symbols.ron` in the current directory like the app.
+//!
+//! It needs none of the app, so it builds without the `gui` feature, and with it without a window,
+//! X11 or the Bevy renderer: `cargo build --no-default-features --bin circuits-export`.
+
+use std::process::ExitCode;
+
+use circuits::export::spice;
+use circuits::import;
+use circuits::project::Project;
+use circuits::symbols;
+
+const USAGE: &str = "Usage: circuits-export [--symbols <library.ron>] <input.ron|.cir|.sp> \
+                     [output.tex|.cir|.sp|.ron]";
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let Ok(library) = take_option(&mut args, "--symbols") else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let mut args = args.into_iter();
+    let (Some(input), output, None) = (args.next(), args.next(), args.next()) else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+
+    // A library given by hand has to be there, the default one is optional like in the app.
+    if let Some(library) = &library {
+        if let Err(err) = std::fs::metadata(library) {
+            eprintln!("Could not load the symbol library {library}: {err}");
+            return ExitCode::FAILURE;
+        }
+    }
+    let library = library.as_deref().unwrap_or(symbols::DEFAULT_LIBRARY);
+    if let Err(err) = symbols::load_library(library) {
+        eprintln!("Could not load the symbol library {library}: {err}");
+        return ExitCode::FAILURE;
+    }
+
+    let text =
+        std::fs::read_to_string(&input).map_err(|err| format!("Could not open {input}: {err}"));
+    let text = match text.and_then(|text| convert(&input, &text, output.as_deref())) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match output {
+        Some(output) => {
+            if let Err(err) = std::fs::write(&output, text) {
+                eprintln!("Could not write {output}: {err}");
+                return ExitCode::FAILURE;
+            }
+        }
+        None => println!("{text}"),
+    }
+    ExitCode::SUCCESS
+}
+
+/// Reads `text`, the contents of `input`, and writes it in the format of the `output` path.
+/// What cannot be converted is reported on stderr.
+fn convert(input: &str, text: &str, output: Option<&str>) -> Result<String, String> {
+    let project = if import::spice::is_deck(input) {
+        let import =
+            import::spice::parse(text).map_err(|err| format!("Could not open {input}: {err}"))?;
+        for reason in &import.skipped {
+            eprintln!("Skipped {reason}");
+        }
+        Project::new(import.records)
+    } else {
+        Project::from_ron(text).map_err(|err| format!("Could not open {input}: {err}"))?
+    };
+
+    Ok(if output.is_some_and(import::spice::is_deck) {
+        let title = std::path::Path::new(input).file_stem().unwrap_or_default();
+        let deck = spice::deck(&project.netlist(), &title.to_string_lossy());
+        for reason in &deck.skipped {
+            eprintln!("Skipped {reason}");
+        }
+        deck.text
+    } else if output.is_some_and(|output| output.ends_with(".ron")) {
+        project.to_ron().map_err(|err| err.to_string())?
+    } else {
+        project.to_latex()
+    })
+}
+
+/// Removes `name` and the value after it from `args`. Fails when the value is missing.
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, ()> {
+    let Some(i) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err(());
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECK: &str = "divider\nV1 1 0 10\nR1 1 2 1k\nR2 2 0 2k\n.end\n";
+
+    #[test]
+    fn converts_between_formats() {
+        let project = convert("divider.cir", DECK, Some("divider.ron")).unwrap();
+        let records = Project::from_ron(&project).unwrap().components;
+        assert_eq!(records.len(), import::spice::parse(DECK).unwrap().records.len());
+
+        let tikz = convert("divider.ron", &project, None).unwrap();
+        assert!(tikz.contains("\\draw"), "{tikz}");
+        assert!(tikz.contains("label=R2"), "{tikz}");
+
+        let deck = convert("divider.ron", &project, Some("out.sp")).unwrap();
+        assert!(deck.starts_with("* divider"), "{deck}");
+        for card in ["V1 ", "R1 ", "R2 "] {
+            assert!(deck.contains(card), "{deck}");
+        }
+    }
+
+    #[test]
+    fn symbols_option() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let mut given = args(&["in.ron", "--symbols", "/ci/symbols.ron", "out.tex"]);
+        assert_eq!(take_option(&mut given, "--symbols"), Ok(Some("/ci/symbols.ron".into())));
+        assert_eq!(given, args(&["in.ron", "out.tex"]));
+
+        let mut absent = args(&["in.ron"]);
+        assert_eq!(take_option(&mut absent, "--symbols"), Ok(None));
+        assert_eq!(absent, args(&["in.ron"]));
+        assert_eq!(take_option(&mut args(&["in.ron", "--symbols"]), "--symbols"), Err(()));
+    }
+
+    #[test]
+    fn reports_unreadable_inputs() {
+        let err = convert("broken.ron", "(version: 1", None).unwrap_err();
+        assert!(err.starts_with("Could not open broken.ron"), "{err}");
+    }
+}
@@ -0,0 +1,308 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::actions::{
+    select_node::{ctrl_pressed, Selected},
+    DeleteComponent,
+};
+use crate::components::Handles;
+use crate::history::Checkpoint;
+use crate::project::{entity_records, spawn_records, ComponentRecord, RecordQuery};
+use crate::structs::{CursorPosition, Position, TikzComponent};
+use crate::GRID_SIZE;
+
+pub struct ClipboardPlugin;
+
+impl Plugin for ClipboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CircuitClipboard::default())
+            .add_observer(copy_selection)
+            .add_observer(paste)
+            .add_systems(Update, clipboard_shortcuts);
+    }
+}
+
+/// Components copied from the canvas, with the grid point they are pasted relative to.
+#[derive(Resource, Default)]
+pub struct CircuitClipboard {
+    records: Vec<ComponentRecord>,
+    origin: Vec2,
+}
+
+/// Copies the selected components and the wires between them. When `cut` is set, they are also
+/// removed from the canvas.
+#[derive(Event)]
+pub struct CopySelection {
+    pub cut: bool,
+}
+
+/// Draws the copied components again, with the origin of the copy at the cursor.
+#[derive(Event)]
+pub struct Paste;
+
+fn is_wire(kind: TikzComponent) -> bool {
+    matches!(kind, TikzComponent::Line | TikzComponent::Dot)
+}
+
+/// Selected components plus the unselected wires between them. The wires are cut at the
+/// terminals of the unselected components: a wire touching one is left out, and so are the
+/// wires left hanging after that, whose ends reach neither a selected component nor another
+/// copied wire.
+fn with_connecting_wires(
+    components: Vec<(Entity, ComponentRecord)>, selected: &HashSet<Entity>,
+) -> Vec<(Entity, ComponentRecord)> {
+    let (mut chosen, rest): (Vec<_>, Vec<_>) = components
+        .into_iter()
+        .partition(|(entity, _)| selected.contains(entity));
+
+    let terminals: HashSet<Position> = chosen
+        .iter()
+        .flat_map(|(_, record)| record.terminals())
+        .map(Position::from)
+        .collect();
+    let (wires, others): (Vec<_>, Vec<_>) = rest
+        .into_iter()
+        .partition(|(_, record)| is_wire(record.kind));
+    let blocked: HashSet<Position> = others
+        .iter()
+        .flat_map(|(_, record)| record.terminals())
+        .map(Position::from)
+        .collect();
+    let ends = |wire: usize| wires[wire].1.terminals().into_iter().map(Position::from);
+
+    let (lines, dots): (Vec<usize>, Vec<usize>) =
+        (0..wires.len()).partition(|&wire| wires[wire].1.kind == TikzComponent::Line);
+    let mut kept: HashSet<usize> = lines
+        .into_iter()
+        .filter(|&wire| !ends(wire).any(|end| blocked.contains(&end)))
+        .collect();
+    // Drops the hanging wires until every end of the others is held.
+    loop {
+        let mut wires_at: HashMap<Position, usize> = HashMap::new();
+        for &wire in &kept {
+            for end in ends(wire) {
+                *wires_at.entry(end).or_default() += 1;
+            }
+        }
+        let hanging: Vec<usize> = kept
+            .iter()
+            .copied()
+            .filter(|&wire| ends(wire).any(|end| !terminals.contains(&end) && wires_at[&end] < 2))
+            .collect();
+        if hanging.is_empty() {
+            break;
+        }
+        for wire in hanging {
+            kept.remove(&wire);
+        }
+    }
+
+    // Only the wires reaching the selection are copied, not loops that hold each other up.
+    let mut reached: HashSet<Position> = terminals.clone();
+    let mut copied: Vec<usize> = Vec::new();
+    loop {
+        let next: Vec<usize> = kept
+            .iter()
+            .copied()
+            .filter(|&wire| ends(wire).any(|end| reached.contains(&end)))
+            .collect();
+        if next.is_empty() {
+            break;
+        }
+        for wire in next {
+            kept.remove(&wire);
+            reached.extend(ends(wire));
+            copied.push(wire);
+        }
+    }
+    // Dots go with the copied wires they join.
+    let joined: HashSet<Position> = copied.iter().flat_map(|&wire| ends(wire)).collect();
+    copied.extend(
+        dots.into_iter()
+            .filter(|&dot| ends(dot).any(|pos| joined.contains(&pos))),
+    );
+
+    copied.sort();
+    chosen.extend(copied.into_iter().map(|wire| wires[wire].clone()));
+    chosen
+}
+
+fn copy_selection(
+    trigger: Trigger<CopySelection>, mut commands: Commands,
+    mut clipboard: ResMut<CircuitClipboard>, components: RecordQuery,
+    selected: Query<Entity, With<Selected>>,
+) {
+    let copied = with_connecting_wires(entity_records(&components), &selected.iter().collect());
+    if copied.is_empty() {
+        return;
+    }
+
+    let origin = copied
+        .iter()
+        .flat_map(|(_, record)| record.terminals())
+        .reduce(Vec2::min)
+        .unwrap_or_default();
+    *clipboard = CircuitClipboard {
+        records: copied.iter().map(|(_, record)| record.clone()).collect(),
+        origin: (origin / GRID_SIZE).floor() * GRID_SIZE,
+    };
+
+    if trigger.event().cut {
+        commands.trigger(Checkpoint);
+        let entities: Vec<_> = copied.into_iter().map(|(entity, _)| entity).collect();
+        commands.trigger_targets(DeleteComponent, entities);
+    }
+}
+
+/// Copied records moved by `offset`. Their labels are dropped, so the pasted components are
+/// numbered after the others instead of repeating the labels of the copied ones.
+fn pasted_records(records: &[ComponentRecord], offset: Vec2) -> Vec<ComponentRecord> {
+    records
+        .iter()
+        .map(|record| ComponentRecord {
+            label: String::new(),
+            ..record.translated(offset)
+        })
+        .collect()
+}
+
+fn paste(
+    _: Trigger<Paste>, mut commands: Commands, clipboard: Res<CircuitClipboard>,
+    cursor_position: Res<CursorPosition>, handles: Res<Handles>,
+    mut material: ResMut<Assets<ColorMaterial>>, selected: Query<Entity, With<Selected>>,
+) {
+    if clipboard.records.is_empty() {
+        return;
+    }
+
+    let records = pasted_records(&clipboard.records, cursor_position.pos - clipboard.origin);
+
+    commands.trigger(Checkpoint);
+    for entity in &selected {
+        commands.entity(entity).remove::<Selected>();
+    }
+    for entity in spawn_records(&mut commands, &records, &handles, &mut material) {
+        commands.entity(entity).insert(Selected);
+    }
+}
+
+fn clipboard_shortcuts(
+    mut commands: Commands, keys: Res<ButtonInput<KeyCode>>,
+    focused: Res<crate::ui::FocusedInputText>,
+) {
+    if focused.0 != Entity::PLACEHOLDER || !ctrl_pressed(&keys) {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyC) {
+        commands.trigger(CopySelection { cut: false });
+    } else if keys.just_pressed(KeyCode::KeyX) {
+        commands.trigger(CopySelection { cut: true });
+    } else if keys.just_pressed(KeyCode::KeyV) {
+        commands.trigger(Paste);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::project::fixtures::{bipole, node, wire};
+    use crate::project::records;
+
+    /// Indices of the records copied when the ones at `selected` are.
+    fn copied(records: Vec<ComponentRecord>, selected: &[u32]) -> Vec<u32> {
+        let components = (0..).map(Entity::from_raw).zip(records).collect();
+        let selected = selected.iter().copied().map(Entity::from_raw).collect();
+        let mut copied: Vec<u32> = with_connecting_wires(components, &selected)
+            .into_iter()
+            .map(|(entity, _)| entity.index())
+            .collect();
+        copied.sort();
+        copied
+    }
+
+    #[test]
+    fn copies_the_wire_between_selected_parts() {
+        let records = vec![
+            bipole(TikzComponent::Resistor, "E1", [0., 0.], [64., 0.]),
+            bipole(TikzComponent::Resistor, "E2", [128., 0.], [192., 0.]),
+            wire([64., 0.], [128., 0.]),
+        ];
+        assert_eq!(copied(records.clone(), &[0, 1]), [0, 1, 2]);
+        // With one end unselected, the wire would hang from the other.
+        assert_eq!(copied(records, &[0]), [0]);
+    }
+
+    #[test]
+    fn cuts_the_branch_to_an_unselected_part() {
+        let records = vec![
+            bipole(TikzComponent::Resistor, "E1", [0., 0.], [64., 0.]),
+            bipole(TikzComponent::Resistor, "E2", [128., 0.], [192., 0.]),
+            bipole(TikzComponent::Resistor, "E3", [96., 64.], [96., 128.]),
+            wire([64., 0.], [96., 0.]),
+            wire([96., 0.], [128., 0.]),
+            node(TikzComponent::Dot, "", [96., 0.]),
+            // The branch down to E3, in two pieces.
+            wire([96., 0.], [96., 32.]),
+            wire([96., 32.], [96., 64.]),
+        ];
+        assert_eq!(copied(records.clone(), &[0, 1]), [0, 1, 3, 4, 5]);
+        assert_eq!(copied(records, &[0, 1, 2]), [0, 1, 2, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn leaves_isolated_wires() {
+        let records = vec![
+            bipole(TikzComponent::Resistor, "E1", [0., 0.], [64., 0.]),
+            wire([0., 64.], [64., 64.]),
+            // A loop that only touches itself, and a wire hanging from E1.
+            wire([128., 0.], [192., 0.]),
+            wire([192., 0.], [128., 0.]),
+            wire([64., 0.], [64., -64.]),
+        ];
+        assert_eq!(copied(records, &[0]), [0]);
+    }
+
+    fn spawn(world: &mut World, records: Vec<ComponentRecord>) {
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      handles: Res<Handles>,
+                      mut material: ResMut<Assets<ColorMaterial>>| {
+                    spawn_records(&mut commands, &records, &handles, &mut material);
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn pasted_components_get_new_labels() {
+        let mut world = World::new();
+        world.add_observer(crate::actions::update_component_label);
+        world.insert_resource(Handles(HashMap::default()));
+        world.insert_resource(Assets::<ColorMaterial>::default());
+        let line = wire([0., 0.], [32., 0.]);
+        spawn(&mut world, vec![line.clone(), line.translated(Vec2::Y * 32.)]);
+
+        let copied = world
+            .run_system_once(|components: RecordQuery| records(&components))
+            .unwrap();
+        assert!(copied.iter().all(|record| !record.label.is_empty()));
+        spawn(&mut world, pasted_records(&copied, Vec2::X * 64.));
+
+        let mut labels: Vec<String> = world
+            .run_system_once(|components: RecordQuery| records(&components))
+            .unwrap()
+            .into_iter()
+            .map(|record| record.label)
+            .collect();
+        assert_eq!(labels.len(), 4);
+        labels.sort();
+        labels.dedup();
+        assert_eq!(labels.len(), 4, "{labels:?}");
+    }
+}
@@ -1,5 +1,7 @@
 use crate::structs::TikzComponent;
+use crate::symbols::{Shape, Symbol, SymbolId};
 use bevy::prelude::*;
+use bevy::render::mesh::PrimitiveTopology;
 use bevy::{render::render_asset::RenderAssetUsages, utils::hashbrown::HashMap};
 
 const CIRCLE_RESOLUTION: usize = 500;
@@ -105,6 +107,397 @@ fn draw_coil() -> Vec<[f32; 3]> {
     circle
 }
 
+/// Vertical coil of `turns` half circles centered on `x`, bulging to the side of `side`.
+fn draw_vertical_coil(x: f32, side: f32, turns: usize, height: f32) -> Vec<[f32; 3]> {
+    let size = 100;
+    let radius = height / (2. * turns as f32);
+    let mut coil = Vec::with_capacity(turns * size);
+    for turn in 0..turns {
+        let center = -height / 2. + radius * (2 * turn + 1) as f32;
+        for i in 0..=size {
+            let angle = std::f32::consts::PI * i as f32 / size as f32;
+            coil.push([x + side * radius * angle.sin(), center + radius * angle.cos(), 0.0]);
+        }
+    }
+    coil
+}
+
+fn line_list(points: Vec<[f32; 3]>) -> Mesh {
+    Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, points)
+}
+
+fn line_strip(points: Vec<[f32; 3]>) -> Mesh {
+    Mesh::new(PrimitiveTopology::LineStrip, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, points)
+}
+
+fn point_list(points: Vec<[f32; 3]>) -> Mesh {
+    Mesh::new(PrimitiveTopology::PointList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, points)
+}
+
+/// Output line of a gate, starting after the inverting bubble when `negated`.
+fn gate_output(negated: bool) -> Vec<Mesh> {
+    let radius = 1. / 8.;
+    if !negated {
+        return vec![line_list(vec![[1.0, 0.0, 0.0], [1.3333, 0.0, 0.0]])];
+    }
+    vec![
+        line_list(vec![[1.0 + 2. * radius, 0.0, 0.0], [1.3333, 0.0, 0.0]]),
+        point_list(draw_circle_from_radius(radius, Vec2::new(1.0 + radius, 0.0))),
+    ]
+}
+
+fn and_port(negated: bool) -> Vec<Mesh> {
+    let l = vec![
+        [0.5, 1.0, 0.0],
+        [-0.6666, 1.0, 0.0],
+        [-0.6666, -1.0, 0.0],
+        [0.5, -1.0, 0.0],
+    ];
+
+    let a2: Vec<_> = CubicBezier::new([[
+        Vec2::new(0.5, 1.0),
+        Vec2::new(1.65 - l[0][0], 0.75),
+        Vec2::new(1.65 - l[0][0], -0.75),
+        Vec2::new(0.5, -1.0),
+    ]])
+    .to_curve()
+    .unwrap()
+    .iter_positions(100)
+    .map(|e| Vec3::new(e.x, e.y, 0.0))
+    .collect();
+    let inputs = vec![
+        [-0.6666, 0.6666, 0.0],
+        [-1.3333, 0.6666, 0.0],
+        [-0.6666, -0.6666, 0.0],
+        [-1.3333, -0.6666, 0.0],
+    ];
+
+    let mut mesh = vec![line_strip(l), point_list(a2.into_iter().map(Into::into).collect())];
+    mesh.push(line_list(inputs));
+    mesh.extend(gate_output(negated));
+    mesh
+}
+
+/// Or port, with the second back arc of the xor when `exclusive`.
+fn or_port(exclusive: bool, negated: bool) -> Vec<Mesh> {
+    let l = [[-0.6666, 1.0, 0.0], [-0.6666, -1.0, 0.0]];
+    let right = Vec3::new(1., 0.0, 0.0);
+    let mut arc = draw_arc(l[0].into(), Vec3::new(0.6666, 1., 0.0), right);
+    let mut lower_arc = draw_arc(l[1].into(), Vec3::new(0.6666, -1., 0.0), right);
+    let mut back_arc = draw_arc(l[0].into(), Vec3::new(0.0, 0.0, 0.0), l[1].into());
+    arc.append(&mut back_arc);
+    arc.append(&mut lower_arc);
+    if exclusive {
+        let mut back_arc = draw_arc(
+            Vec3::from_array(l[0]) + Vec3::new(-0.2, 0.0, 0.0),
+            Vec3::new(-0.2, 0.0, 0.0),
+            Vec3::from_array(l[1]) + Vec3::new(-0.2, 0.0, 0.0),
+        );
+        arc.append(&mut back_arc);
+    }
+    let inputs = vec![
+        [-0.5, 0.6666, 0.0],
+        [-1.3333, 0.6666, 0.0],
+        [-0.5, -0.6666, 0.0],
+        [-1.3333, -0.6666, 0.0],
+    ];
+
+    let mut mesh = vec![point_list(arc), line_list(inputs)];
+    mesh.extend(gate_output(negated));
+    mesh
+}
+
+/// Bipolar transistor with the base on the left. The emitter is below on the npn and above on
+/// the pnp, with the arrow pointing out of and into the base.
+fn bjt(pnp: bool) -> Vec<Mesh> {
+    let e = if pnp { 1. } else { -1. };
+    let arrow = if pnp {
+        [
+            [-0.4, 0.2 * e, 0.0],
+            [-0.24, 0.24 * e, 0.0],
+            [-0.4, 0.2 * e, 0.0],
+            [-0.34, 0.35 * e, 0.0],
+        ]
+    } else {
+        [
+            [0.0, 0.5 * e, 0.0],
+            [-0.16, 0.46 * e, 0.0],
+            [0.0, 0.5 * e, 0.0],
+            [-0.06, 0.35 * e, 0.0],
+        ]
+    };
+    let mut lines = vec![
+        // Base
+        [-1.3333, 0.0, 0.0],
+        [-0.4, 0.0, 0.0],
+        [-0.4, 0.45, 0.0],
+        [-0.4, -0.45, 0.0],
+        // Collector
+        [-0.4, -0.2 * e, 0.0],
+        [0.0, -0.5 * e, 0.0],
+        [0.0, -0.5 * e, 0.0],
+        [0.0, -1.3333 * e, 0.0],
+        // Emitter
+        [-0.4, 0.2 * e, 0.0],
+        [0.0, 0.5 * e, 0.0],
+        [0.0, 0.5 * e, 0.0],
+        [0.0, 1.3333 * e, 0.0],
+    ];
+    lines.extend(arrow);
+    vec![line_list(lines), Annulus::new(0.68, 0.7).into()]
+}
+
+/// Mosfet with the gate on the left. The drain is above on the nmos and below on the pmos, which
+/// also has the bubble on the gate.
+fn mos(pmos: bool) -> Vec<Mesh> {
+    let gate_end = if pmos { -0.66 } else { -0.5 };
+    let lines = vec![
+        // Gate
+        [-1.3333, 0.0, 0.0],
+        [gate_end, 0.0, 0.0],
+        [-0.5, 0.45, 0.0],
+        [-0.5, -0.45, 0.0],
+        // Channel
+        [-0.35, 0.5, 0.0],
+        [-0.35, -0.5, 0.0],
+        // Upper terminal
+        [-0.35, 0.4, 0.0],
+        [0.0, 0.4, 0.0],
+        [0.0, 0.4, 0.0],
+        [0.0, 1.3333, 0.0],
+        // Lower terminal
+        [-0.35, -0.4, 0.0],
+        [0.0, -0.4, 0.0],
+        [0.0, -0.4, 0.0],
+        [0.0, -1.3333, 0.0],
+    ];
+    let mut mesh = vec![line_list(lines)];
+    if pmos {
+        mesh.push(point_list(draw_circle_from_radius(0.08, Vec2::new(-0.58, 0.0))));
+    }
+    mesh
+}
+
+enum DiodeMark {
+    None,
+    Zener,
+    Light,
+}
+
+fn diode(mark: DiodeMark) -> Vec<Mesh> {
+    let mut lines = vec![
+        [-0.5, 0.0, 0.0],
+        [-0.2, 0.0, 0.0],
+        // Triangle
+        [-0.2, 0.3, 0.0],
+        [-0.2, -0.3, 0.0],
+        [-0.2, 0.3, 0.0],
+        [0.2, 0.0, 0.0],
+        [-0.2, -0.3, 0.0],
+        [0.2, 0.0, 0.0],
+        // Bar
+        [0.2, 0.3, 0.0],
+        [0.2, -0.3, 0.0],
+        [0.2, 0.0, 0.0],
+        [0.5, 0.0, 0.0],
+    ];
+    match mark {
+        DiodeMark::None => {}
+        DiodeMark::Zener => lines.extend([
+            [0.2, 0.3, 0.0],
+            [0.1, 0.3, 0.0],
+            [0.2, -0.3, 0.0],
+            [0.3, -0.3, 0.0],
+        ]),
+        // Two arrows going out of the diode.
+        DiodeMark::Light => {
+            for x in [-0.05, 0.1] {
+                lines.extend([
+                    [x, 0.35, 0.0],
+                    [x + 0.2, 0.55, 0.0],
+                    [x + 0.2, 0.55, 0.0],
+                    [x + 0.1, 0.53, 0.0],
+                    [x + 0.2, 0.55, 0.0],
+                    [x + 0.18, 0.45, 0.0],
+                ]);
+            }
+        }
+    }
+    vec![line_list(lines)]
+}
+
+/// Single cell, with the longer plate on the positive side.
+fn battery() -> Vec<Mesh> {
+    vec![line_list(vec![
+        [-0.5, 0.0, 0.0],
+        [-0.06, 0.0, 0.0],
+        [-0.06, 0.4, 0.0],
+        [-0.06, -0.4, 0.0],
+        [0.06, 0.2, 0.0],
+        [0.06, -0.2, 0.0],
+        [0.06, 0.0, 0.0],
+        [0.5, 0.0, 0.0],
+    ])]
+}
+
+fn sine(amplitude: f32, y: f32) -> Mesh {
+    let points = (0..=50)
+        .map(|i| {
+            let t = i as f32 / 50.;
+            let x = -0.25 + 0.5 * t;
+            [x, y + amplitude * (std::f32::consts::TAU * t).sin(), 0.0]
+        })
+        .collect();
+    line_strip(points)
+}
+
+/// Sinusoidal source, with the arrow of the current when `current`.
+fn sine_source(current: bool) -> Vec<Mesh> {
+    let mut mesh = vec![Annulus::new(0.45, 0.5).into()];
+    if current {
+        mesh.push(sine(0.08, 0.18));
+        mesh.push(line_list(
+            ARROW.iter().map(|[x, y, z]| [*x, y - 0.15, *z]).collect(),
+        ));
+    } else {
+        mesh.push(sine(0.15, 0.0));
+    }
+    mesh
+}
+
+/// Diamond of the dependent sources, with the signs or the arrow inside.
+fn controlled_source(current: bool) -> Vec<Mesh> {
+    let diamond = line_strip(vec![
+        [-0.5, 0.0, 0.0],
+        [0.0, 0.5, 0.0],
+        [0.5, 0.0, 0.0],
+        [0.0, -0.5, 0.0],
+        [-0.5, 0.0, 0.0],
+    ]);
+    if current {
+        return vec![diamond, line_list(ARROW.to_vec())];
+    }
+    let plus = PLUS.iter().map(|[x, y, z]| [x + 0.2, *y, *z]).collect();
+    let minus = MINUS.iter().map(|[x, y, z]| [x - 0.2, *y, *z]).collect();
+    vec![diamond, line_list(plus), line_list(minus)]
+}
+
+/// Switch that is open, or closed when `closed`.
+fn switch(closed: bool) -> Vec<Mesh> {
+    let blade_end = if closed { [0.3, 0.05, 0.0] } else { [0.28, 0.3, 0.0] };
+    let contacts = [Vec2::new(-0.3, 0.0), Vec2::new(0.3, 0.0)]
+        .into_iter()
+        .flat_map(|center| draw_circle_from_radius(0.04, center))
+        .collect();
+    vec![
+        line_list(vec![
+            [-0.5, 0.0, 0.0],
+            [-0.34, 0.0, 0.0],
+            [-0.3, 0.0, 0.0],
+            blade_end,
+            [0.34, 0.0, 0.0],
+            [0.5, 0.0, 0.0],
+        ]),
+        point_list(contacts),
+    ]
+}
+
+fn lamp() -> Vec<Mesh> {
+    vec![
+        Annulus::new(0.45, 0.5).into(),
+        line_list(vec![
+            [-0.33, 0.33, 0.0],
+            [0.33, -0.33, 0.0],
+            [-0.33, -0.33, 0.0],
+            [0.33, 0.33, 0.0],
+        ]),
+    ]
+}
+
+fn fuse() -> Vec<Mesh> {
+    vec![
+        line_strip(vec![
+            [-0.3, 0.12, 0.0],
+            [0.3, 0.12, 0.0],
+            [0.3, -0.12, 0.0],
+            [-0.3, -0.12, 0.0],
+            [-0.3, 0.12, 0.0],
+        ]),
+        line_list(vec![[-0.5, 0.0, 0.0], [0.5, 0.0, 0.0]]),
+    ]
+}
+
+const AMMETER_LETTER: [[f32; 3]; 6] = [
+    [-0.15, -0.2, 0.0],
+    [0.0, 0.2, 0.0],
+    [0.0, 0.2, 0.0],
+    [0.15, -0.2, 0.0],
+    [-0.08, -0.02, 0.0],
+    [0.08, -0.02, 0.0],
+];
+
+const VOLTMETER_LETTER: [[f32; 3]; 4] = [
+    [-0.15, 0.2, 0.0],
+    [0.0, -0.2, 0.0],
+    [0.0, -0.2, 0.0],
+    [0.15, 0.2, 0.0],
+];
+
+/// Circle of the meters with the letter of what is measured.
+fn meter(letter: &[[f32; 3]]) -> Vec<Mesh> {
+    vec![Annulus::new(0.45, 0.5).into(), line_list(letter.to_vec())]
+}
+
+/// Resistor crossed by an arrow.
+fn variable_resistor() -> Vec<Mesh> {
+    vec![
+        line_strip(RESISTOR.to_vec()),
+        line_list(vec![
+            [-0.3, -0.35, 0.0],
+            [0.3, 0.35, 0.0],
+            [0.3, 0.35, 0.0],
+            [0.16, 0.3, 0.0],
+            [0.3, 0.35, 0.0],
+            [0.28, 0.2, 0.0],
+        ]),
+    ]
+}
+
+/// Meshes of a symbol of the library. Its geometry is in grid cells, while the meshes are scaled
+/// by the size of the components.
+fn symbol_meshes(symbol: &Symbol) -> Vec<Mesh> {
+    let to_mesh = |point: Vec2| {
+        let point = point / 1.5;
+        [point.x, point.y, 0.0]
+    };
+    let arc = |center: Vec2, radius: f32, start: f32, end: f32| {
+        let steps = 64;
+        (0..=steps)
+            .map(|i| {
+                let angle = (start + (end - start) * i as f32 / steps as f32).to_radians();
+                to_mesh(center + radius * Vec2::from_angle(angle))
+            })
+            .collect()
+    };
+    symbol
+        .geometry
+        .iter()
+        .map(|shape| match shape {
+            Shape::Polyline(points) => line_strip(points.iter().copied().map(to_mesh).collect()),
+            Shape::Arc {
+                center,
+                radius,
+                start,
+                end,
+            } => line_strip(arc(*center, *radius, *start, *end)),
+            Shape::Circle { center, radius } => line_strip(arc(*center, *radius, 0., 360.)),
+        })
+        .collect()
+}
+
 #[derive(Resource)]
 pub struct Handles(pub HashMap<TikzComponent, InfoMeshes>);
 
@@ -188,76 +581,14 @@ pub fn load_handles(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
         map.insert(TikzComponent::Ground, InfoMeshes { meshes: mesh });
     }
 
-    {
-        // AND PORT
-        let l = vec![
-            [0.5, 1.0, 0.0],
-            [-0.6666, 1.0, 0.0],
-            [-0.6666, -1.0, 0.0],
-            [0.5, -1.0, 0.0],
-        ];
-
-        let a2: Vec<_> = CubicBezier::new([[
-            Vec2::new(0.5, 1.0),
-            Vec2::new(1.65 - l[0][0], 0.75),
-            Vec2::new(1.65 - l[0][0], -0.75),
-            Vec2::new(0.5, -1.0),
-        ]])
-        .to_curve()
-        .unwrap()
-        .iter_positions(100)
-        .map(|e| Vec3::new(e.x, e.y, 0.0))
-        .collect();
-        let line = Mesh::new(Topology::LineStrip, RenderAssetUsages::RENDER_WORLD)
-            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, l);
-        let arc = Mesh::new(Topology::PointList, RenderAssetUsages::RENDER_WORLD)
-            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, a2);
-        let lines = Mesh::new(Topology::LineList, RenderAssetUsages::RENDER_WORLD)
-            .with_inserted_attribute(
-                Mesh::ATTRIBUTE_POSITION,
-                vec![
-                    [-0.6666, 0.6666, 0.0],
-                    [-1.3333, 0.6666, 0.0],
-                    [-0.6666, -0.6666, 0.0],
-                    [-1.3333, -0.6666, 0.0],
-                    [1.0, 0.0, 0.0],
-                    [1.3333, 0.0, 0.0],
-                ],
-            );
-        let mesh = vec![
-            meshes.add(line).into(),
-            meshes.add(arc).into(),
-            meshes.add(lines).into(),
-        ];
-
-        map.insert(TikzComponent::AndGate, InfoMeshes { meshes: mesh });
+    for (kind, negated) in [(TikzComponent::AndGate, false), (TikzComponent::NandGate, true)] {
+        let mesh = and_port(negated).into_iter().map(|m| meshes.add(m).into()).collect();
+        map.insert(kind, InfoMeshes { meshes: mesh });
     }
 
-    {
-        // OR PORT
-        let l = [[-0.6666, 1.0, 0.0], [-0.6666, -1.0, 0.0]];
-        let right = Vec3::new(1., 0.0, 0.0);
-        let mut arc = draw_arc(l[0].into(), Vec3::new(0.6666, 1., 0.0), right);
-        let mut lower_arc = draw_arc(l[1].into(), Vec3::new(0.6666, -1., 0.0), right);
-        let mut back_arc = draw_arc(l[0].into(), Vec3::new(0.0, 0.0, 0.0), l[1].into());
-        arc.append(&mut back_arc);
-        arc.append(&mut lower_arc);
-        let arcs = Mesh::new(Topology::PointList, RenderAssetUsages::RENDER_WORLD)
-            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, arc);
-        let lines = Mesh::new(Topology::LineList, RenderAssetUsages::RENDER_WORLD)
-            .with_inserted_attribute(
-                Mesh::ATTRIBUTE_POSITION,
-                vec![
-                    [-0.5, 0.6666, 0.0],
-                    [-1.3333, 0.6666, 0.0],
-                    [-0.5, -0.6666, 0.0],
-                    [-1.3333, -0.6666, 0.0],
-                    [1.0, 0.0, 0.0],
-                    [1.3333, 0.0, 0.0],
-                ],
-            );
-        let mesh = vec![meshes.add(arcs).into(), meshes.add(lines).into()];
-        map.insert(TikzComponent::OrGate, InfoMeshes { meshes: mesh });
+    for (kind, negated) in [(TikzComponent::OrGate, false), (TikzComponent::NorGate, true)] {
+        let mesh = or_port(false, negated).into_iter().map(|m| meshes.add(m).into()).collect();
+        map.insert(kind, InfoMeshes { meshes: mesh });
     }
 
     {
@@ -299,38 +630,9 @@ pub fn load_handles(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
         map.insert(TikzComponent::NotGate, info);
     }
 
-    {
-        // XOR PORT
-        let l = [[-0.6666, 1.0, 0.0], [-0.6666, -1.0, 0.0]];
-        let right = Vec3::new(1., 0.0, 0.0);
-        let mut upper_arc = draw_arc(l[0].into(), Vec3::new(0.6666, 1., 0.0), right);
-        let mut lower_arc = draw_arc(l[1].into(), Vec3::new(0.6666, -1., 0.0), right);
-        let mut back_arc = draw_arc(l[0].into(), Vec3::new(0.0, 0.0, 0.0), l[1].into());
-        upper_arc.append(&mut back_arc);
-        upper_arc.append(&mut lower_arc);
-        let mut back_arc = draw_arc(
-            Vec3::from_array(l[0]) + Vec3::new(-0.2, 0.0, 0.0),
-            Vec3::new(-0.2, 0.0, 0.0),
-            Vec3::from_array(l[1]) + Vec3::new(-0.2, 0.0, 0.0),
-        );
-        upper_arc.append(&mut back_arc);
-        let arcs = Mesh::new(Topology::PointList, RenderAssetUsages::RENDER_WORLD)
-            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, upper_arc);
-        let lines = Mesh::new(Topology::LineList, RenderAssetUsages::RENDER_WORLD)
-            .with_inserted_attribute(
-                Mesh::ATTRIBUTE_POSITION,
-                vec![
-                    [-0.5, 0.6666, 0.0],
-                    [-1.3333, 0.6666, 0.0],
-                    [-0.5, -0.6666, 0.0],
-                    [-1.3333, -0.6666, 0.0],
-                    [1.0, 0.0, 0.0],
-                    [1.3333, 0.0, 0.0],
-                ],
-            );
-        let mesh = vec![meshes.add(arcs).into(), meshes.add(lines).into()];
-        let info = InfoMeshes { meshes: mesh };
-        map.insert(TikzComponent::XorGate, info);
+    for (kind, negated) in [(TikzComponent::XorGate, false), (TikzComponent::XnorGate, true)] {
+        let mesh = or_port(true, negated).into_iter().map(|m| meshes.add(m).into()).collect();
+        map.insert(kind, InfoMeshes { meshes: mesh });
     }
 
     {
@@ -373,5 +675,86 @@ pub fn load_handles(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
         let info = InfoMeshes { meshes: mesh };
         map.insert(TikzComponent::AmpOp, info);
     }
+    for (kind, mesh) in [
+        (TikzComponent::Transistor, bjt(false)),
+        (TikzComponent::Pnp, bjt(true)),
+        (TikzComponent::Nmos, mos(false)),
+        (TikzComponent::Pmos, mos(true)),
+        (TikzComponent::Zener, diode(DiodeMark::Zener)),
+        (TikzComponent::Led, diode(DiodeMark::Light)),
+        (TikzComponent::Battery, battery()),
+        (TikzComponent::SinVSource, sine_source(false)),
+        (TikzComponent::SinISource, sine_source(true)),
+        (TikzComponent::ControlledVSource, controlled_source(false)),
+        (TikzComponent::ControlledISource, controlled_source(true)),
+        (TikzComponent::Switch, switch(false)),
+        (TikzComponent::ClosedSwitch, switch(true)),
+        (TikzComponent::Lamp, lamp()),
+        (TikzComponent::Fuse, fuse()),
+        (TikzComponent::Ammeter, meter(&AMMETER_LETTER)),
+        (TikzComponent::Voltmeter, meter(&VOLTMETER_LETTER)),
+        (TikzComponent::VariableResistor, variable_resistor()),
+    ] {
+        let mesh = mesh.into_iter().map(|m| meshes.add(m).into()).collect();
+        map.insert(kind, InfoMeshes { meshes: mesh });
+    }
+
+    {
+        // DIODE, from the anode on the left to the cathode on the right
+        let mesh = diode(DiodeMark::None)
+            .into_iter()
+            .map(|m| meshes.add(m).into())
+            .collect();
+        map.insert(TikzComponent::Diode, InfoMeshes { meshes: mesh });
+    }
+
+    {
+        // TRANSFORMER
+        let mut coils = draw_vertical_coil(-0.3, 1., 4, 1.3333);
+        coils.append(&mut draw_vertical_coil(0.3, -1., 4, 1.3333));
+        let coils = Mesh::new(Topology::PointList, RenderAssetUsages::RENDER_WORLD)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, coils);
+        let lines = Mesh::new(Topology::LineList, RenderAssetUsages::RENDER_WORLD)
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_POSITION,
+                vec![
+                    // Primary
+                    [-1.3333, 1.3333, 0.0],
+                    [-0.3, 1.3333, 0.0],
+                    [-0.3, 1.3333, 0.0],
+                    [-0.3, 0.6666, 0.0],
+                    [-1.3333, -1.3333, 0.0],
+                    [-0.3, -1.3333, 0.0],
+                    [-0.3, -1.3333, 0.0],
+                    [-0.3, -0.6666, 0.0],
+                    // Secondary
+                    [1.3333, 1.3333, 0.0],
+                    [0.3, 1.3333, 0.0],
+                    [0.3, 1.3333, 0.0],
+                    [0.3, 0.6666, 0.0],
+                    [1.3333, -1.3333, 0.0],
+                    [0.3, -1.3333, 0.0],
+                    [0.3, -1.3333, 0.0],
+                    [0.3, -0.6666, 0.0],
+                    // Core
+                    [-0.05, 0.7, 0.0],
+                    [-0.05, -0.7, 0.0],
+                    [0.05, 0.7, 0.0],
+                    [0.05, -0.7, 0.0],
+                ],
+            );
+        let mesh = vec![meshes.add(coils).into(), meshes.add(lines).into()];
+        map.insert(TikzComponent::Transformer, InfoMeshes { meshes: mesh });
+    }
+    for kind in SymbolId::all().map(TikzComponent::Custom) {
+        let TikzComponent::Custom(id) = kind else {
+            continue;
+        };
+        let mesh = symbol_meshes(id.symbol())
+            .into_iter()
+            .map(|m| meshes.add(m).into())
+            .collect();
+        map.insert(kind, InfoMeshes { meshes: mesh });
+    }
     commands.insert_resource(Handles(map));
 }
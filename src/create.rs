@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 
-use crate::structs::{ComponentLabel, ComponentStructure, Info, Position, TikzComponent};
+use crate::structs::{
+    ComponentLabel, ComponentStructure, Info, Position, TikzComponent, ValueStyle,
+};
 use crate::*;
 
 #[derive(Event)]
@@ -10,6 +12,7 @@ pub fn create(
     _: Trigger<ConvertCircuit>, _commands: Commands,
     components: Query<(Entity, &ComponentStructure, &TikzComponent, &Info)>,
     parents: Query<&ComponentLabel>, children: Query<(&GlobalTransform, &Parent, &ComponentLabel)>,
+    value_style: Res<ValueStyle>,
 ) {
     let mut pos_label = HashMap::new();
     for (child_transform, parent, child_label) in &children {
@@ -65,13 +68,12 @@ pub fn create(
     for (ent, component, c_type, info) in &components {
         let parent_label = parents.get(ent).unwrap();
         let c_label = parent_label.get_label();
-        let c_info = info.get_component_info();
-        let c_type = c_type.tikz_type();
+        let inside = info.tikz_options(*c_type, *value_style);
         match component {
             ComponentStructure::Node(position) => {
                 let position = (*position).into();
                 buffer.push_str(&format!(
-                    "{label} node[{c_type}{c_info}]{c_label}{{}}\n",
+                    "{label} node[{inside}]{c_label}{{}}\n",
                     label = map_to_label(position),
                 ));
             }
@@ -79,7 +81,7 @@ pub fn create(
                 let initial = (*initial).into();
                 let fin = (*fin).into();
                 buffer.push_str(&format!(
-                    "{label} to[{c_type}{c_info}] {final_label}\n",
+                    "{label} to[{inside}] {final_label}\n",
                     label = map_to_label(initial),
                     final_label = map_to_label(fin),
                 ));
@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::structs::ValueStyle;
+
+/// What the `\draw ... ;` of the circuit is exported inside of.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Wrapper {
+    /// The path alone, for a `circuitikz` environment the document already has.
+    #[default]
+    Bare,
+    Environment,
+    /// A floating `figure` with the caption and the label of the profile.
+    Figure,
+    /// A document of its own, compiled to a figure the size of the circuit.
+    Standalone,
+}
+
+impl Wrapper {
+    /// The wrapper after this one, going back to [`Wrapper::Bare`] after the last.
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Bare => Self::Environment,
+            Self::Environment => Self::Figure,
+            Self::Figure => Self::Standalone,
+            Self::Standalone => Self::Bare,
+        }
+    }
+}
+
+/// How the circuit is written out, saved with the project.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExportProfile {
+    pub wrapper: Wrapper,
+    /// Caption of the figure, left out when empty.
+    #[serde(default)]
+    pub caption: String,
+    /// Key the figure is referenced by, like `fig:divider`, left out when empty.
+    #[serde(default)]
+    pub label: String,
+}
+
+/// Preamble of a document with the circuit, loading circuitikz with siunitx when the values need
+/// it. Bipoles are drawn the length they have on the canvas, a grid square and a half.
+pub fn preamble(value_style: ValueStyle) -> String {
+    let options = match value_style {
+        ValueStyle::Siunitx => "[siunitx]",
+        ValueStyle::Plain => "",
+    };
+    format!("\\usepackage{options}{{circuitikz}}\n\\ctikzset{{bipoles/length=0.75cm}}\n")
+}
+
+fn environment(draw: &str) -> String {
+    format!("\\begin{{circuitikz}}\n{draw}\n\\end{{circuitikz}}\n")
+}
+
+/// Wraps the `\draw ... ;` of a circuit as `profile` asks.
+pub fn wrap(draw: &str, profile: &ExportProfile, value_style: ValueStyle) -> String {
+    match profile.wrapper {
+        Wrapper::Bare => draw.to_string(),
+        Wrapper::Environment => environment(draw),
+        Wrapper::Figure => {
+            let mut text = format!("\\begin{{figure}}[h]\n\\centering\n{}", environment(draw));
+            if !profile.caption.is_empty() {
+                text.push_str(&format!("\\caption{{{}}}\n", profile.caption));
+            }
+            if !profile.label.is_empty() {
+                text.push_str(&format!("\\label{{{}}}\n", profile.label));
+            }
+            text.push_str("\\end{figure}\n");
+            text
+        }
+        Wrapper::Standalone => format!(
+            "\\documentclass{{standalone}}\n{}\\begin{{document}}\n{}\\end{{document}}\n",
+            preamble(value_style),
+            environment(draw)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DRAW: &str = "\\draw (0,0) to[R] (1,0);";
+
+    fn profile(wrapper: Wrapper, caption: &str, label: &str) -> ExportProfile {
+        ExportProfile {
+            wrapper,
+            caption: caption.to_string(),
+            label: label.to_string(),
+        }
+    }
+
+    #[test]
+    fn preambles() {
+        assert_eq!(
+            preamble(ValueStyle::Siunitx),
+            "\\usepackage[siunitx]{circuitikz}\n\\ctikzset{bipoles/length=0.75cm}\n"
+        );
+        assert_eq!(
+            preamble(ValueStyle::Plain),
+            "\\usepackage{circuitikz}\n\\ctikzset{bipoles/length=0.75cm}\n"
+        );
+    }
+
+    #[test]
+    fn bare() {
+        let profile = profile(Wrapper::Bare, "Divider", "fig:divider");
+        assert_eq!(wrap(DRAW, &profile, ValueStyle::Siunitx), DRAW);
+    }
+
+    #[test]
+    fn circuitikz_environment() {
+        let profile = profile(Wrapper::Environment, "Divider", "fig:divider");
+        assert_eq!(
+            wrap(DRAW, &profile, ValueStyle::Siunitx),
+            "\\begin{circuitikz}\n\\draw (0,0) to[R] (1,0);\n\\end{circuitikz}\n"
+        );
+    }
+
+    #[test]
+    fn figure() {
+        let profile = profile(Wrapper::Figure, "Divider", "fig:divider");
+        assert_eq!(
+            wrap(DRAW, &profile, ValueStyle::Siunitx),
+            "\\begin{figure}[h]\n\\centering\n\
+             \\begin{circuitikz}\n\\draw (0,0) to[R] (1,0);\n\\end{circuitikz}\n\
+             \\caption{Divider}\n\\label{fig:divider}\n\\end{figure}\n"
+        );
+    }
+
+    #[test]
+    fn figure_without_caption_or_label() {
+        let profile = profile(Wrapper::Figure, "", "");
+        assert_eq!(
+            wrap(DRAW, &profile, ValueStyle::Siunitx),
+            "\\begin{figure}[h]\n\\centering\n\
+             \\begin{circuitikz}\n\\draw (0,0) to[R] (1,0);\n\\end{circuitikz}\n\\end{figure}\n"
+        );
+    }
+
+    #[test]
+    fn standalone() {
+        // The caption and the label only go in a figure.
+        let profile = profile(Wrapper::Standalone, "Divider", "fig:divider");
+        assert_eq!(
+            wrap(DRAW, &profile, ValueStyle::Plain),
+            "\\documentclass{standalone}\n\
+             \\usepackage{circuitikz}\n\\ctikzset{bipoles/length=0.75cm}\n\
+             \\begin{document}\n\
+             \\begin{circuitikz}\n\\draw (0,0) to[R] (1,0);\n\\end{circuitikz}\n\
+             \\end{document}\n"
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+
+use crate::project::ProjectFile;
+#[cfg(feature = "gui")]
+use bevy::math::Affine3A;
+#[cfg(feature = "gui")]
+use crate::{
+    actions::{
+        draw_components::ActualComponent,
+        select_node::{Selected, HIGHLIGHT},
+    },
+    graph::netlist::Netlist,
+    project::{records, RecordQuery},
+    structs::TikzComponent,
+};
+
+pub mod latex;
+pub mod spice;
+#[cfg(feature = "gui")]
+pub mod svg;
+
+#[cfg(feature = "gui")]
+pub struct ExportPlugin;
+
+#[cfg(feature = "gui")]
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(latex::ExportProfile::default())
+            .add_observer(export_spice)
+            .add_observer(export_svg);
+    }
+}
+
+/// Path of the project file with another extension.
+pub fn sibling_path(file: &ProjectFile, extension: &str) -> String {
+    std::path::Path::new(&file.0)
+        .with_extension(extension)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Writes the circuit as a SPICE deck beside the project file, with the `.cir` extension.
+#[derive(Event)]
+pub struct ExportSpice;
+
+#[cfg(feature = "gui")]
+fn export_spice(_: Trigger<ExportSpice>, file: Res<ProjectFile>, components: RecordQuery) {
+    let path = sibling_path(&file, "cir");
+    let netlist = Netlist::build(&records(&components));
+    let title = std::path::Path::new(&file.0)
+        .file_stem()
+        .unwrap_or_default();
+    let deck = spice::deck(&netlist, &title.to_string_lossy());
+    for reason in &deck.skipped {
+        warn!("Skipped {reason}");
+    }
+    match std::fs::write(&path, deck.text) {
+        Ok(()) => info!("Exported SPICE deck to {path}"),
+        Err(err) => error!("Could not write {path}: {err}"),
+    }
+}
+
+/// Writes the drawing of the canvas as an SVG figure beside the project file, with the `.svg`
+/// extension. Only the components are drawn, without the grid, the cursor or the overlays.
+#[derive(Event)]
+pub struct ExportSvg;
+
+/// Moves the parts of a selected component back to where they are when it is not selected, since
+/// the selection draws the child holding them bigger. `local` and `global` are the transforms of
+/// that child.
+#[cfg(feature = "gui")]
+fn unselected(local: &Transform, global: &GlobalTransform) -> Affine3A {
+    let mut unscaled = *local;
+    unscaled.scale /= HIGHLIGHT;
+    let parent = global.affine() * local.compute_affine().inverse();
+    parent * unscaled.compute_affine() * global.affine().inverse()
+}
+
+#[cfg(feature = "gui")]
+fn export_svg(
+    _: Trigger<ExportSvg>, file: Res<ProjectFile>, meshes: Res<Assets<Mesh>>,
+    components: Query<(Entity, Has<Selected>), With<TikzComponent>>, children: Query<&Children>,
+    actual: Query<(&Transform, &GlobalTransform), With<ActualComponent>>,
+    parts: Query<(
+        Option<&Mesh2d>,
+        Option<&Sprite>,
+        Option<&Text2d>,
+        Option<&TextFont>,
+        &GlobalTransform,
+        &InheritedVisibility,
+    )>,
+) {
+    let mut shapes = Vec::new();
+    let mut components: Vec<(Entity, bool)> = components.iter().collect();
+    components.sort();
+    for (entity, selected) in components {
+        for &child in children.get(entity).into_iter().flatten() {
+            let correction = match actual.get(child) {
+                Ok((local, global)) if selected => unselected(local, global),
+                _ => Affine3A::IDENTITY,
+            };
+            for part in std::iter::once(child).chain(children.iter_descendants(child)) {
+                let Ok((mesh, sprite, text, font, transform, visibility)) = parts.get(part) else {
+                    continue;
+                };
+                if !visibility.get() {
+                    continue;
+                }
+                let transform = GlobalTransform::from(correction * transform.affine());
+                if let Some(mesh) = mesh.and_then(|mesh| meshes.get(&mesh.0)) {
+                    shapes.extend(svg::mesh_shapes(mesh, &transform));
+                }
+                if let Some(sprite) = sprite {
+                    let size = sprite.custom_size.unwrap_or(Vec2::ONE);
+                    shapes.push(svg::sprite_shape(size, &transform));
+                }
+                if let Some(text) = text {
+                    let font_size = font.cloned().unwrap_or_default().font_size;
+                    shapes.extend(svg::text_shapes(&text.0, font_size, &transform));
+                }
+            }
+        }
+    }
+
+    let path = sibling_path(&file, "svg");
+    match std::fs::write(&path, svg::document(&shapes)) {
+        Ok(()) => info!("Exported SVG to {path}"),
+        Err(err) => error!("Could not write {path}: {err}"),
+    }
+}
+
+#[cfg(all(test, feature = "gui"))]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn selection_is_not_exported() {
+        // A component turned a quarter at (32, 16), whose selected child is drawn bigger.
+        let component =
+            Transform::from_xyz(32., 16., 0.).with_rotation(Quat::from_rotation_z(FRAC_PI_2));
+        let mut child = Transform::from_xyz(0., 0., 1.);
+        let unselected_part = GlobalTransform::from(component)
+            .mul_transform(child)
+            .mul_transform(Transform::from_xyz(8., 0., 0.));
+        child.scale *= HIGHLIGHT;
+        let global = GlobalTransform::from(component).mul_transform(child);
+        let part = global.mul_transform(Transform::from_xyz(8., 0., 0.));
+
+        let correction = unselected(&child, &global);
+        let corrected = GlobalTransform::from(correction * part.affine());
+        assert!(corrected.affine().abs_diff_eq(unselected_part.affine(), 1e-4));
+        assert!(corrected.translation().abs_diff_eq(Vec3::new(32., 24., 1.), 1e-4));
+    }
+}
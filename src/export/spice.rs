@@ -0,0 +1,116 @@
+use bevy::utils::HashMap;
+
+use crate::graph::netlist::{NetComponent, Netlist};
+use crate::structs::{spice_value, TikzComponent};
+
+/// A SPICE deck, with the components it could not describe.
+pub struct SpiceDeck {
+    pub text: String,
+    /// Labels of the components left out, with why.
+    pub skipped: Vec<String>,
+}
+
+/// Letter of the card and the pins in the order SPICE reads them. Voltage sources have their
+/// positive side at the end, and current sources push current from the start to the end.
+fn card(kind: TikzComponent) -> Option<(char, [&'static str; 2])> {
+    use TikzComponent::*;
+    Some(match kind {
+        Resistor | VariableResistor => ('R', ["start", "end"]),
+        Capacitor => ('C', ["start", "end"]),
+        Inductor => ('L', ["start", "end"]),
+        VSource | Battery | SinVSource | Ammeter => ('V', ["end", "start"]),
+        ISource | SinISource => ('I', ["start", "end"]),
+        _ => return None,
+    })
+}
+
+/// Number of the node of a net in the deck: `0` for ground and `N3` becomes `3`.
+fn node(netlist: &Netlist, component: &NetComponent, pin: &str) -> String {
+    let terminal = component.terminal(pin).unwrap();
+    let name = &netlist.net(terminal.net).name;
+    name.trim_start_matches('N').to_string()
+}
+
+/// Writes one card for every resistor, capacitor, inductor and source. Cards are numbered by their
+/// letter in the order the components were drawn. Sine sources take their value as the amplitude of
+/// an AC analysis, and ammeters become 0 V sources, as SPICE measures currents through them.
+pub fn deck(netlist: &Netlist, title: &str) -> SpiceDeck {
+    let mut text = format!("* {title}\n");
+    let mut skipped = Vec::new();
+    let mut counts: HashMap<char, usize> = HashMap::default();
+
+    for component in &netlist.components {
+        let record = &component.record;
+        if record.kind == TikzComponent::Ground {
+            continue;
+        }
+        let Some((letter, [positive, negative])) = card(record.kind) else {
+            skipped.push(format!("{} ({}) has no SPICE card", record.label, record.kind));
+            continue;
+        };
+        let value = match (record.kind, record.info.value) {
+            (TikzComponent::Ammeter, _) => "0".to_string(),
+            (_, Some(value)) => spice_value(value),
+            (_, None) => {
+                skipped.push(format!("{} ({}) has no value", record.label, record.kind));
+                continue;
+            }
+        };
+        let value = match record.kind {
+            TikzComponent::SinVSource | TikzComponent::SinISource => format!("DC 0 AC {value}"),
+            _ => value,
+        };
+
+        let count = counts.entry(letter).or_insert(0);
+        *count += 1;
+        text.push_str(&format!(
+            "{letter}{count} {} {} {value}\n",
+            node(netlist, component, positive),
+            node(netlist, component, negative),
+        ));
+    }
+
+    for reason in &skipped {
+        text.push_str(&format!("* Skipped {reason}\n"));
+    }
+    text.push_str(".end\n");
+    SpiceDeck { text, skipped }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::fixtures::{bipole, node, valued};
+
+    #[test]
+    fn writes_cards() {
+        use TikzComponent::*;
+        let records = [
+            node(Ground, "GND1", [0., 0.]),
+            valued(10., bipole(VSource, "E1", [0., 0.], [0., 64.])),
+            valued(4701.2, bipole(Resistor, "E2", [0., 64.], [64., 64.])),
+            bipole(Ammeter, "E3", [64., 64.], [64., 0.]),
+            node(Ground, "GND2", [64., 0.]),
+            valued(1.5, bipole(SinVSource, "E4", [128., 0.], [128., 64.])),
+            valued(1e-3, bipole(ISource, "E5", [128., 64.], [192., 64.])),
+            bipole(Capacitor, "E6", [192., 64.], [192., 0.]),
+            node(AndGate, "AND1", [256., 0.]),
+        ];
+        let deck = deck(&Netlist::build(&records), "divider");
+        // Ground is node 0 and the others keep the number of their net. Voltage sources start
+        // from their positive end, the ammeter is a 0 V source and the sine one only has AC.
+        let cards = [
+            "* divider",
+            "V1 1 0 10",
+            "R1 1 2 4.7012k",
+            "V2 0 2 0",
+            "V3 4 3 DC 0 AC 1.5",
+            "I1 4 5 1m",
+            "* Skipped E6 (Capacitor) has no value",
+            "* Skipped AND1 (And) has no SPICE card",
+            ".end",
+        ];
+        assert_eq!(deck.text, cards.map(|card| format!("{card}\n")).concat());
+        assert_eq!(deck.skipped, ["E6 (Capacitor) has no value", "AND1 (And) has no SPICE card"]);
+    }
+}
@@ -0,0 +1,399 @@
+use bevy::{
+    prelude::*,
+    render::mesh::{PrimitiveTopology, VertexAttributeValues},
+};
+
+/// Width of every stroke, the width of the wires on the canvas.
+const STROKE: f32 = 0.5;
+/// Points of a point list closer than this are joined into a curve.
+const JOIN: f32 = 1.5;
+/// Points of a curve closer than this to the last kept one are dropped.
+const DETAIL: f32 = 0.25;
+/// Margin around the drawing.
+const MARGIN: f32 = 8.;
+/// Size of a world unit in the written figure, so it opens at about the size of the canvas.
+const ZOOM: f32 = 3.;
+
+/// Something drawn on the canvas, in world coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Polyline(Vec<Vec2>),
+    Ring {
+        center: Vec2,
+        radius: f32,
+        width: f32,
+    },
+    /// A thin sprite, like a wire.
+    Line {
+        from: Vec2,
+        to: Vec2,
+        width: f32,
+    },
+    Polygon(Vec<Vec2>),
+    Text {
+        pos: Vec2,
+        content: String,
+        size: f32,
+        angle: f32,
+    },
+}
+
+/// Points of `points` in the order of a curve through them, breaking it where the closest point
+/// left is too far. Point lists of the meshes are not always in order, like the circles whose
+/// points go around by a radian each.
+fn chain(mut points: Vec<Vec2>) -> Vec<Vec<Vec2>> {
+    let mut curves = Vec::new();
+    while let Some(start) = points.pop() {
+        let mut curve = vec![start];
+        loop {
+            let last = *curve.last().unwrap();
+            let closest = points
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| a.distance(last).total_cmp(&b.distance(last)));
+            match closest {
+                Some((i, point)) if point.distance(last) < JOIN => {
+                    curve.push(points.swap_remove(i));
+                }
+                _ => break,
+            }
+        }
+        curves.push(curve);
+    }
+    curves
+}
+
+/// Curve without the points that add no detail, keeping its ends.
+fn simplify(curve: Vec<Vec2>) -> Vec<Vec2> {
+    let Some(&end) = curve.last() else {
+        return curve;
+    };
+    let mut kept: Vec<Vec2> = Vec::new();
+    for point in curve {
+        if kept
+            .last()
+            .is_none_or(|last| last.distance(point) >= DETAIL)
+        {
+            kept.push(point);
+        }
+    }
+    if kept.last() != Some(&end) {
+        kept.push(end);
+    }
+    kept
+}
+
+/// Shapes of a mesh placed by `transform`. Line lists and strips are kept as they are, point lists
+/// are joined into curves and triangle meshes, which are only used for annuli, become rings.
+pub fn mesh_shapes(mesh: &Mesh, transform: &GlobalTransform) -> Vec<Shape> {
+    let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    else {
+        return Vec::new();
+    };
+    let points: Vec<Vec2> = positions
+        .iter()
+        .map(|&position| transform.transform_point(position.into()).truncate())
+        .collect();
+    match mesh.primitive_topology() {
+        PrimitiveTopology::LineList => points
+            .chunks_exact(2)
+            .map(|pair| Shape::Polyline(pair.to_vec()))
+            .collect(),
+        PrimitiveTopology::LineStrip => vec![Shape::Polyline(simplify(points))],
+        PrimitiveTopology::PointList => chain(points)
+            .into_iter()
+            .map(|curve| Shape::Polyline(simplify(curve)))
+            .collect(),
+        PrimitiveTopology::TriangleList | PrimitiveTopology::TriangleStrip => {
+            // The middle of the bounds, since the seam of an annulus repeats its vertices.
+            let min = points.iter().fold(Vec2::INFINITY, |min, &p| min.min(p));
+            let max = points.iter().fold(Vec2::NEG_INFINITY, |max, &p| max.max(p));
+            let center = min.midpoint(max);
+            let distances = points.iter().map(|point| point.distance(center));
+            let inner = distances.clone().fold(f32::INFINITY, f32::min);
+            let outer = distances.fold(0., f32::max);
+            vec![Shape::Ring {
+                center,
+                radius: (inner + outer) / 2.,
+                width: outer - inner,
+            }]
+        }
+    }
+}
+
+/// Shape of a sprite of `size` placed by `transform`: a line when it is thin, like the wires and
+/// the leads of the bipoles, or its outline otherwise.
+pub fn sprite_shape(size: Vec2, transform: &GlobalTransform) -> Shape {
+    let center = transform.translation().truncate();
+    let x = transform
+        .affine()
+        .transform_vector3(Vec3::X * size.x)
+        .truncate();
+    let y = transform
+        .affine()
+        .transform_vector3(Vec3::Y * size.y)
+        .truncate();
+    let (long, short) = if x.length() >= y.length() {
+        (x, y)
+    } else {
+        (y, x)
+    };
+    if long.length() >= 2. * short.length() {
+        return Shape::Line {
+            from: center - long / 2.,
+            to: center + long / 2.,
+            width: short.length(),
+        };
+    }
+    Shape::Polygon(
+        [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)]
+            .map(|(a, b)| center + (x * a + y * b) / 2.)
+            .to_vec(),
+    )
+}
+
+/// Lines of a text placed by `transform`, centered on it like the texts of the canvas.
+pub fn text_shapes(content: &str, font_size: f32, transform: &GlobalTransform) -> Vec<Shape> {
+    let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+    let size = font_size * scale.y.abs();
+    let angle = rotation.to_euler(EulerRot::ZYX).0;
+    let lines: Vec<&str> = content.lines().collect();
+    let top = (lines.len() as f32 - 1.) / 2.;
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| Shape::Text {
+            pos: translation.truncate()
+                + Vec2::from_angle(angle).rotate(Vec2::Y * (top - i as f32) * size),
+            content: line.to_string(),
+            size,
+            angle,
+        })
+        .collect()
+}
+
+/// Number with at most two decimals, without trailing zeros.
+fn number(value: f32) -> String {
+    let text = format!("{value:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        text => text.to_string(),
+    }
+}
+
+/// SVG point of a world point, whose y grows upwards.
+fn point(p: Vec2) -> String {
+    format!("{},{}", number(p.x), number(-p.y))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Writes the shapes as an SVG figure, in black on a transparent background.
+pub fn document(shapes: &[Shape]) -> String {
+    let mut min = Vec2::INFINITY;
+    let mut max = Vec2::NEG_INFINITY;
+    let mut include = |p: Vec2, margin: f32| {
+        min = min.min(p - margin);
+        max = max.max(p + margin);
+    };
+    for shape in shapes {
+        match shape {
+            Shape::Polyline(points) | Shape::Polygon(points) => {
+                points.iter().for_each(|&p| include(p, 0.))
+            }
+            Shape::Ring { center, radius, .. } => include(*center, *radius),
+            Shape::Line { from, to, .. } => {
+                include(*from, 0.);
+                include(*to, 0.);
+            }
+            Shape::Text {
+                pos, content, size, ..
+            } => include(*pos, *size * content.chars().count().max(1) as f32 / 2.),
+        }
+    }
+    if shapes.is_empty() {
+        (min, max) = (Vec2::ZERO, Vec2::ZERO);
+    }
+    let (min, max) = (min - MARGIN, max + MARGIN);
+    let size = max - min;
+
+    let mut text = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n",
+        number(min.x),
+        number(-max.y),
+        number(size.x),
+        number(size.y),
+        number(size.x * ZOOM),
+        number(size.y * ZOOM),
+    );
+    text.push_str(&format!(
+        "<g fill=\"none\" stroke=\"black\" stroke-width=\"{}\" stroke-linecap=\"round\" stroke-linejoin=\"round\">\n",
+        number(STROKE)
+    ));
+    for shape in shapes {
+        match shape {
+            Shape::Polyline(points) if points.len() == 1 => {
+                let p = point(points[0]);
+                text.push_str(&format!("<path d=\"M{p} L{p}\"/>\n"));
+            }
+            Shape::Polyline(points) => {
+                let points: Vec<String> = points.iter().map(|&p| point(p)).collect();
+                text.push_str(&format!("<polyline points=\"{}\"/>\n", points.join(" ")));
+            }
+            Shape::Ring {
+                center,
+                radius,
+                width,
+            } => text.push_str(&format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" stroke-width=\"{}\"/>\n",
+                number(center.x),
+                number(-center.y),
+                number(*radius),
+                number(*width)
+            )),
+            Shape::Line { from, to, width } => text.push_str(&format!(
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke-width=\"{}\" stroke-linecap=\"square\"/>\n",
+                number(from.x),
+                number(-from.y),
+                number(to.x),
+                number(-to.y),
+                number(*width)
+            )),
+            Shape::Polygon(points) => {
+                let points: Vec<String> = points.iter().map(|&p| point(p)).collect();
+                text.push_str(&format!(
+                    "<polygon points=\"{}\" fill=\"black\" stroke=\"none\"/>\n",
+                    points.join(" ")
+                ));
+            }
+            Shape::Text {
+                pos,
+                content,
+                size,
+                angle,
+            } => {
+                let rotation = if angle.abs() > f32::EPSILON {
+                    format!(
+                        " transform=\"rotate({} {} {})\"",
+                        number(-angle.to_degrees()),
+                        number(pos.x),
+                        number(-pos.y)
+                    )
+                } else {
+                    String::new()
+                };
+                text.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\" font-size=\"{}\" font-family=\"sans-serif\" text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"black\" stroke=\"none\"{rotation}>{}</text>\n",
+                    number(pos.x),
+                    number(-pos.y),
+                    number(*size),
+                    escape(content)
+                ));
+            }
+        }
+    }
+    text.push_str("</g>\n</svg>\n");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn points(points: &[(f32, f32)]) -> Vec<Vec2> {
+        points.iter().map(|&(x, y)| Vec2::new(x, y)).collect()
+    }
+
+    #[test]
+    fn chains_close_points() {
+        let curves = chain(points(&[(0., 0.), (1., 0.), (2.2, 0.), (10., 0.), (10.5, 0.)]));
+        assert_eq!(
+            curves,
+            [points(&[(10.5, 0.), (10., 0.)]), points(&[(2.2, 0.), (1., 0.), (0., 0.)])]
+        );
+        assert!(chain(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn simplifies_keeping_the_ends() {
+        let curve = points(&[(0., 0.), (0.1, 0.), (0.2, 0.), (1., 0.), (1.1, 0.)]);
+        assert_eq!(simplify(curve), points(&[(0., 0.), (1., 0.), (1.1, 0.)]));
+        assert_eq!(simplify(points(&[(3., 3.)])), points(&[(3., 3.)]));
+        assert!(simplify(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn sprites() {
+        // A wire turned upright stays a line, along its long side.
+        let transform = GlobalTransform::from(
+            Transform::from_xyz(5., 5., 0.).with_rotation(Quat::from_rotation_z(FRAC_PI_2)),
+        );
+        let Shape::Line { from, to, width } = sprite_shape(Vec2::new(10., 1.), &transform) else {
+            panic!("a thin sprite is a line");
+        };
+        assert!(from.abs_diff_eq(Vec2::new(5., 0.), 1e-4), "{from}");
+        assert!(to.abs_diff_eq(Vec2::new(5., 10.), 1e-4), "{to}");
+        assert!((width - 1.).abs() < 1e-4);
+
+        let transform =
+            GlobalTransform::from(Transform::from_xyz(1., 2., 0.).with_scale(Vec3::splat(2.)));
+        assert_eq!(
+            sprite_shape(Vec2::new(2., 3.), &transform),
+            Shape::Polygon(points(&[(-1., -1.), (3., -1.), (3., 5.), (-1., 5.)]))
+        );
+    }
+
+    #[test]
+    fn empty_document() {
+        assert_eq!(
+            document(&[]),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"-8 -8 16 16\" width=\"48\" height=\"48\">\n\
+             <g fill=\"none\" stroke=\"black\" stroke-width=\"0.5\" stroke-linecap=\"round\" stroke-linejoin=\"round\">\n\
+             </g>\n</svg>\n"
+        );
+    }
+
+    #[test]
+    fn document_shapes() {
+        let text = document(&[
+            Shape::Line {
+                from: Vec2::ZERO,
+                to: Vec2::new(10., 0.),
+                width: 0.75,
+            },
+            Shape::Polyline(points(&[(1., 2.)])),
+            Shape::Polyline(points(&[(0., 0.), (2.5, -1.)])),
+            Shape::Ring {
+                center: Vec2::new(4., 0.),
+                radius: 2.,
+                width: 0.5,
+            },
+            Shape::Text {
+                pos: Vec2::new(0., 4.),
+                content: "a<b".to_string(),
+                size: 2.,
+                angle: FRAC_PI_2,
+            },
+        ]);
+        // The y axis points down in SVG, and the bounds take in the text and a margin.
+        assert!(text.contains("viewBox=\"-11 -15 29 25\" width=\"87\" height=\"75\""), "{text}");
+        for element in [
+            "<line x1=\"0\" y1=\"0\" x2=\"10\" y2=\"0\" stroke-width=\"0.75\" stroke-linecap=\"square\"/>",
+            "<path d=\"M1,-2 L1,-2\"/>",
+            "<polyline points=\"0,0 2.5,1\"/>",
+            "<circle cx=\"4\" cy=\"0\" r=\"2\" stroke-width=\"0.5\"/>",
+            " transform=\"rotate(-90 0 -4)\">a&lt;b</text>",
+        ] {
+            assert!(text.contains(element), "{element} not in {text}");
+        }
+    }
+}
@@ -1,5 +1,6 @@
+#[cfg(feature = "gui")]
+use bevy::input::common_conditions::input_just_pressed;
 use bevy::{
-    input::common_conditions::input_just_pressed,
     prelude::*,
     utils::{hashbrown::HashMap, HashSet},
 };
@@ -9,49 +10,43 @@ use petgraph::{
     Graph,
 };
 
-use crate::structs::{ComponentLabel, ComponentStructure, Position};
+pub mod netlist;
+pub mod routing;
+pub mod rules;
 
+use crate::structs::{
+    BuildInfo, ComponentLabel, ComponentStructure, Info, Position, TikzComponent, ValueStyle,
+};
+
+#[cfg(feature = "gui")]
 pub struct GraphPlugin;
 
+#[cfg(feature = "gui")]
 impl Plugin for GraphPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(CircuitGraph::default())
-            .insert_resource(LabelChildComponent {
-                map: HashMap::default(),
-            })
+            .insert_resource(LabelChildComponent::default())
             .add_observer(add_to_graph)
             .add_observer(remove_from_graph)
             .add_observer(update_child_label::<AddToGraph>)
             .add_observer(update_child_label::<RemoveFromGraph>)
+            .add_observer(update_child_label::<UpdateChildLabels>)
             .add_systems(Update, testing.run_if(input_just_pressed(KeyCode::Space)));
     }
 }
 #[derive(Event)]
 pub struct AddToGraph(pub ComponentStructure, pub Entity);
 
+#[cfg(feature = "gui")]
 fn add_to_graph(trigger: Trigger<AddToGraph>, mut graph: ResMut<CircuitGraph>) {
     let AddToGraph(structure, entity) = *trigger.event();
-
-    let (initial, fin) = match structure {
-        ComponentStructure::Node(pos_v) => {
-            let pos = Position::from(pos_v);
-            let index = graph.get_index_or_add(pos);
-            (index, index)
-        }
-        ComponentStructure::To([in_pos_v, fin_pos_v]) => {
-            let in_pos = Position::from(in_pos_v);
-            let fin_pos = Position::from(fin_pos_v);
-            let in_index = graph.get_index_or_add(in_pos);
-            let fin_index = graph.get_index_or_add(fin_pos);
-            (in_index, fin_index)
-        }
-    };
-    graph.add_edge(initial, fin, entity);
+    graph.add_component(structure, entity);
 }
 
 #[derive(Event)]
 pub struct RemoveFromGraph(pub Entity);
 
+#[cfg(feature = "gui")]
 fn remove_from_graph(trigger: Trigger<RemoveFromGraph>, mut graph: ResMut<CircuitGraph>) {
     let entity = trigger.event().0;
     if let Some(edge_to_remove) = graph
@@ -63,12 +58,21 @@ fn remove_from_graph(trigger: Trigger<RemoveFromGraph>, mut graph: ResMut<Circui
     }
 }
 
-#[derive(Resource, Debug)]
-struct LabelChildComponent {
+/// Recomputes where the pins are, after nodes are moved or turned.
+#[derive(Event)]
+pub struct UpdateChildLabels;
+
+/// Maps the position of every pin to the label it is called by, like `AND1.in 1`.
+#[derive(Resource, Debug, Default)]
+pub struct LabelChildComponent {
     map: HashMap<Position, Coordinate>,
 }
 
 impl LabelChildComponent {
+    pub fn insert(&mut self, pos: Position, label: String) {
+        self.map.insert(pos, Coordinate::Label(label));
+    }
+
     #[inline]
     fn coord_from(&self, pos: Position) -> Coordinate {
         match self.map.get(&pos) {
@@ -78,24 +82,21 @@ impl LabelChildComponent {
     }
 }
 
+#[cfg(feature = "gui")]
 fn update_child_label<E: Event>(
     _: Trigger<E>, mut child_labels: ResMut<LabelChildComponent>,
-    parents: Query<&crate::structs::ComponentLabel>,
-    children: Query<(&GlobalTransform, &Parent, &crate::structs::ComponentLabel)>,
+    parents: Query<(&ComponentLabel, &TikzComponent, &ComponentStructure, Option<&BuildInfo>)>,
 ) {
     child_labels.map.clear();
-    for (child_transform, parent, child_label) in &children {
-        info!("Position {:?} with label {}", child_transform.translation(), child_label);
-        let parent_label = parents.get(**parent).unwrap();
-        child_labels.map.insert(
-            child_transform.translation().truncate().into(),
-            Coordinate::Label(format!("{}{}", parent_label.label, child_label.label)),
-        );
+    for (parent_label, cc, structure, build_info) in &parents {
+        for (pin, pos) in structure.pins(*cc, build_info.copied().unwrap_or_default()) {
+            child_labels.insert(pos.into(), format!("{}{pin}", parent_label.label));
+        }
     }
 }
 
 #[derive(Resource, Default)]
-struct CircuitGraph {
+pub struct CircuitGraph {
     indexes: Vec<NodeIndex>,
     positions: Vec<Position>,
     graph: Graph<Position, Entity, petgraph::Directed>,
@@ -117,6 +118,24 @@ impl CircuitGraph {
         index
     }
 
+    pub fn add_component(&mut self, structure: ComponentStructure, entity: Entity) -> EdgeIndex {
+        let (initial, fin) = match structure {
+            ComponentStructure::Node(pos_v) => {
+                let pos = Position::from(pos_v);
+                let index = self.get_index_or_add(pos);
+                (index, index)
+            }
+            ComponentStructure::To([in_pos_v, fin_pos_v]) => {
+                let in_pos = Position::from(in_pos_v);
+                let fin_pos = Position::from(fin_pos_v);
+                let in_index = self.get_index_or_add(in_pos);
+                let fin_index = self.get_index_or_add(fin_pos);
+                (in_index, fin_index)
+            }
+        };
+        self.add_edge(initial, fin, entity)
+    }
+
     pub fn add_edge(&mut self, a: NodeIndex, b: NodeIndex, entity: Entity) -> EdgeIndex {
         self.graph.add_edge(a, b, entity)
     }
@@ -126,14 +145,36 @@ impl CircuitGraph {
     }
 }
 
+#[cfg(feature = "gui")]
 fn testing(
     mut commands: Commands, graph: Res<CircuitGraph>, child_labels: Res<LabelChildComponent>,
-    components: Query<(&crate::structs::TikzComponent, &crate::structs::Info, &ComponentLabel)>,
+    components: Query<(Entity, &TikzComponent, &Info, &ComponentLabel, Option<&BuildInfo>)>,
+    value_style: Res<ValueStyle>, export_profile: Res<crate::export::latex::ExportProfile>,
 ) {
+    let components = components
+        .iter()
+        .map(|(entity, cc, info, label, build_info)| {
+            (entity, (*cc, info, label, build_info.copied().unwrap_or_default()))
+        })
+        .collect();
+    let buffer = export_tikz(&graph, &child_labels, &components, *value_style);
+    let buffer = crate::export::latex::wrap(&buffer, &export_profile, *value_style);
+
+    // Validate nodes
+    commands.trigger(crate::ui::UpdateCircuitText { text: buffer });
+}
+
+/// Writes the circuit as a single `\draw ... ;` path, walking the graph from each node.
+pub fn export_tikz(
+    graph: &CircuitGraph, child_labels: &LabelChildComponent,
+    components: &HashMap<Entity, (TikzComponent, &Info, &ComponentLabel, BuildInfo)>,
+    value_style: ValueStyle,
+) -> String {
     let mut buffer = "\\draw\n".to_string();
     let mut coord_labels: HashMap<Position, Coordinate> = HashMap::default();
     let mut seen_edges = HashSet::default();
 
+    let mut steps = Vec::new();
     let mut last_target = Position { x: -1000, y: -1000 };
 
     for node in graph.graph.node_indices() {
@@ -169,16 +210,17 @@ fn testing(
                 }
             }
 
-            let (cc, info, parent_label) = components.get(entity).unwrap();
+            let &(cc, info, parent_label, build_info) = components.get(&entity).unwrap();
             let parent_label = parent_label.get_label();
-            let c_type = cc.tikz_type();
             let node_or_to = if cc.is_single() { "node" } else { "to" };
-            let c_info = info.get_component_info();
-            let inside = format!("{}{}", c_type, c_info);
+            let mut inside = info.tikz_options(cc, value_style);
+            if cc.is_single() {
+                inside.push_str(&build_info.node_options());
+            }
 
-            let coord1 = find_coord(source.into(), None, &child_labels, &coord_labels);
+            let coord1 = find_coord(source.into(), None, child_labels, &coord_labels);
             let coord2 =
-                find_coord(target.into(), Some(source.into()), &child_labels, &coord_labels);
+                find_coord(target.into(), Some(source.into()), child_labels, &coord_labels);
 
             let hidden = source == last_target;
 
@@ -200,7 +242,14 @@ fn testing(
                 format!("{s_coord2} {coordinate}")
             };
 
-            buffer.push_str(&format!(" {s_coord1} {node_or_to}[{inside}] {end}\n"));
+            let wire = (cc == TikzComponent::Line && inside == cc.tikz_type()).then(|| Wire {
+                start: s_coord1.clone(),
+                source,
+                target,
+                end: coord2.clone(),
+                coordinate: coordinate.clone(),
+            });
+            steps.push((format!(" {s_coord1} {node_or_to}[{inside}] {end}\n"), wire));
             last_target = target;
 
             if !coordinate.is_empty() {
@@ -208,15 +257,64 @@ fn testing(
             }
         }
     }
+    // Wires that turn once are joined into a single `-|` or `|-`.
+    let mut steps = steps.into_iter().peekable();
+    while let Some((text, wire)) = steps.next() {
+        let joined = wire.and_then(|first| {
+            let joins =
+                |next: &Option<Wire>| next.as_ref().is_some_and(|n| first.corner(n).is_some());
+            let (_, second) = steps.next_if(|(_, next)| joins(next))?;
+            Some((first, second?))
+        });
+        let Some((first, second)) = joined else {
+            buffer.push_str(&text);
+            continue;
+        };
+        let operation = first.corner(&second).unwrap_or_default();
+        let end = second.end.coords(CoordinateOptions {
+            relative_to: Some(Coordinate::Position(first.source)),
+            hidden: false,
+            with_parens: true,
+        });
+        buffer.push_str(&format!(" {} {operation} {end} {}\n", first.start, second.coordinate));
+    }
     buffer.push(';');
+    buffer
+}
 
-    // Validate nodes
-    commands.trigger(crate::ui::UpdateCircuitText { text: buffer });
+/// A plain wire of the exported path.
+struct Wire {
+    /// Where the step starts, empty when it goes on from the last one.
+    start: String,
+    source: Position,
+    target: Position,
+    end: Coordinate,
+    /// The `coordinate (A1)` given to its end.
+    coordinate: String,
+}
+
+impl Wire {
+    /// Path operation going through both wires when the next one goes on from the end of this
+    /// one, which has no coordinate, turning from it.
+    fn corner(&self, next: &Wire) -> Option<&'static str> {
+        if !self.coordinate.is_empty() || !next.start.is_empty() {
+            return None;
+        }
+        let horizontal = |wire: &Wire| wire.source.y == wire.target.y && wire.source != wire.target;
+        let vertical = |wire: &Wire| wire.source.x == wire.target.x && wire.source != wire.target;
+        if horizontal(self) && vertical(next) {
+            Some("-|")
+        } else if vertical(self) && horizontal(next) {
+            Some("|-")
+        } else {
+            None
+        }
+    }
 }
 
 fn find_coord(
     coordinate: Coordinate, last_position: Option<Coordinate>,
-    child_labels: &Res<LabelChildComponent>, coord_labels: &HashMap<Position, Coordinate>,
+    child_labels: &LabelChildComponent, coord_labels: &HashMap<Position, Coordinate>,
 ) -> Coordinate {
     if let Coordinate::Label(_) = coordinate {
         return coordinate;
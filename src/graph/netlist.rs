@@ -0,0 +1,353 @@
+use bevy::math::Vec2;
+use bevy::utils::HashMap;
+
+use crate::project::{ComponentRecord, Project};
+use crate::structs::{ComponentStructure, Position, TikzComponent};
+
+/// Index of a net in [`Netlist::nets`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NetId(pub usize);
+
+/// Points that are electrically the same, after wires and dots are collapsed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Net {
+    /// `0` for the net of the grounds, `N1`, `N2`... for the others.
+    pub name: String,
+    /// Every terminal and wire end on the net, sorted.
+    pub positions: Vec<Position>,
+}
+
+/// Where a component touches a net.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Terminal {
+    /// Anchor of the terminal: `start` and `end` for bipoles, the pin name without its dot for
+    /// nodes, like `in 1` or `B`, and `center` for nodes without pins.
+    pub pin: &'static str,
+    pub position: Position,
+    pub net: NetId,
+}
+
+/// A component that is not a wire or a dot, with the nets it connects.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetComponent {
+    /// Index of the component in the records the netlist was built from.
+    pub index: usize,
+    pub record: ComponentRecord,
+    pub terminals: Vec<Terminal>,
+}
+
+impl NetComponent {
+    pub fn terminal(&self, pin: &str) -> Option<&Terminal> {
+        self.terminals.iter().find(|terminal| terminal.pin == pin)
+    }
+}
+
+/// Electrical view of a circuit: which terminals are connected to which.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Netlist {
+    pub nets: Vec<Net>,
+    pub components: Vec<NetComponent>,
+    positions: HashMap<Position, NetId>,
+}
+
+/// Disjoint sets of points, merged by wires, dots and grounds.
+struct Sets(Vec<usize>);
+
+impl Sets {
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.0[i] != i {
+            self.0[i] = self.0[self.0[i]];
+            i = self.0[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        // The smaller root stays, so nets keep the order their first point was seen in.
+        self.0[a.max(b)] = a.min(b);
+    }
+}
+
+/// Distance from `p` to the segment from `a` to `b`, which may be a single point.
+pub(crate) fn segment_distance(p: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let length = (b - a).length_squared();
+    if length == 0. {
+        return p.distance(a);
+    }
+    let t = ((p - a).dot(b - a) / length).clamp(0., 1.);
+    p.distance(a + (b - a) * t)
+}
+
+/// If `p` lies on the segment from `a` to `b`, not counting its ends.
+pub(crate) fn inside_segment(p: Position, a: Position, b: Position) -> bool {
+    p != a && p != b && segment_distance(p.into(), a.into(), b.into()) < 1e-3
+}
+
+/// Terminals of a record, with their anchor names.
+pub(crate) fn terminals(record: &ComponentRecord) -> Vec<(&'static str, Position)> {
+    match record.structure {
+        ComponentStructure::To([initial, fin]) => {
+            vec![("start", initial.into()), ("end", fin.into())]
+        }
+        ComponentStructure::Node(pos) => {
+            let pins = record
+                .structure
+                .pins(record.kind, record.build_info.unwrap_or_default());
+            if pins.is_empty() {
+                return vec![("center", pos.into())];
+            }
+            pins.into_iter()
+                .map(|(pin, pos)| (pin.trim_start_matches('.'), pos.into()))
+                .collect()
+        }
+    }
+}
+
+impl Netlist {
+    /// Builds the nets of `records`. Wires join their ends, and a terminal that touches the middle
+    /// of a wire joins it too. Wires crossing each other stay apart. Every ground is the same net.
+    pub fn build(records: &[ComponentRecord]) -> Self {
+        let mut points: Vec<Position> = Vec::new();
+        let mut ids: HashMap<Position, usize> = HashMap::default();
+        let mut point = |pos: Position| {
+            *ids.entry(pos).or_insert_with(|| {
+                points.push(pos);
+                points.len() - 1
+            })
+        };
+
+        let all: Vec<Vec<(usize, (&str, Position))>> = records
+            .iter()
+            .map(|record| {
+                terminals(record)
+                    .into_iter()
+                    .map(|(pin, pos)| (point(pos), (pin, pos)))
+                    .collect()
+            })
+            .collect();
+
+        let mut sets = Sets((0..points.len()).collect());
+        let mut ground = None;
+        for (record, terminals) in records.iter().zip(&all) {
+            match record.kind {
+                TikzComponent::Line => sets.union(terminals[0].0, terminals[1].0),
+                TikzComponent::Ground => {
+                    let id = terminals[0].0;
+                    sets.union(*ground.get_or_insert(id), id);
+                }
+                _ => {}
+            }
+        }
+        for (record, terminals) in records.iter().zip(&all) {
+            let (TikzComponent::Line, [(a, (_, start)), (_, (_, end))]) =
+                (record.kind, terminals.as_slice())
+            else {
+                continue;
+            };
+            for (i, &pos) in points.iter().enumerate() {
+                if inside_segment(pos, *start, *end) {
+                    sets.union(*a, i);
+                }
+            }
+        }
+
+        let mut netlist = Self::default();
+        let mut roots: HashMap<usize, NetId> = HashMap::default();
+        let ground = ground.map(|id| sets.find(id));
+        let mut count = 0;
+        for (i, &pos) in points.iter().enumerate() {
+            // Roots are the first point of their set, so nets are named in the order they appear.
+            let root = sets.find(i);
+            let net = *roots.entry(root).or_insert_with(|| {
+                let name = if Some(root) == ground {
+                    "0".to_string()
+                } else {
+                    count += 1;
+                    format!("N{count}")
+                };
+                netlist.nets.push(Net {
+                    name,
+                    positions: Vec::new(),
+                });
+                NetId(netlist.nets.len() - 1)
+            });
+            netlist.nets[net.0].positions.push(pos);
+            netlist.positions.insert(pos, net);
+        }
+        for net in &mut netlist.nets {
+            net.positions.sort();
+        }
+
+        for (index, (record, terminals)) in records.iter().zip(all).enumerate() {
+            if matches!(record.kind, TikzComponent::Line | TikzComponent::Dot) {
+                continue;
+            }
+            let terminals = terminals
+                .into_iter()
+                .map(|(_, (pin, position))| Terminal {
+                    pin,
+                    position,
+                    net: netlist.positions[&position],
+                })
+                .collect();
+            netlist.components.push(NetComponent {
+                index,
+                record: record.clone(),
+                terminals,
+            });
+        }
+        netlist
+    }
+
+    pub fn net(&self, id: NetId) -> &Net {
+        &self.nets[id.0]
+    }
+
+    /// Net of a terminal or wire end at `pos`.
+    pub fn net_at(&self, pos: Position) -> Option<NetId> {
+        self.positions.get(&pos).copied()
+    }
+
+    pub fn net_named(&self, name: &str) -> Option<NetId> {
+        self.nets.iter().position(|net| net.name == name).map(NetId)
+    }
+
+    /// Net called by `reference`, either its name, like `N2`, or a terminal, like `E3.end`.
+    pub fn resolve(&self, reference: &str) -> Option<NetId> {
+        let reference = reference.trim();
+        if let Some(net) = self.net_named(reference) {
+            return Some(net);
+        }
+        let (label, pin) = reference.split_once('.')?;
+        self.components
+            .iter()
+            .find(|component| component.record.label == label)?
+            .terminal(pin)
+            .map(|terminal| terminal.net)
+    }
+
+    /// Net of the grounds, if the circuit has one.
+    pub fn ground(&self) -> Option<NetId> {
+        self.net_named("0")
+    }
+
+    /// Components with a terminal on `net`, with the terminal.
+    pub fn terminals_on(&self, net: NetId) -> impl Iterator<Item = (&NetComponent, &Terminal)> {
+        self.components.iter().flat_map(move |component| {
+            component
+                .terminals
+                .iter()
+                .filter(move |terminal| terminal.net == net)
+                .map(move |terminal| (component, terminal))
+        })
+    }
+}
+
+/// One line per net with the terminals on it, like `N1: E1.end S1.B`.
+impl std::fmt::Display for Netlist {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, net) in self.nets.iter().enumerate() {
+            write!(f, "{}:", net.name)?;
+            for (component, terminal) in self.terminals_on(NetId(i)) {
+                write!(f, " {}.{}", component.record.label, terminal.pin)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Project {
+    pub fn netlist(&self) -> Netlist {
+        Netlist::build(&self.components)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::fixtures::{bipole, ground, node, wire};
+
+    #[test]
+    fn t_junction_joins_the_wires() {
+        let records = [
+            wire([0., 0.], [64., 0.]),
+            // Starts on the middle of the first wire.
+            wire([32., 0.], [32., -32.]),
+            bipole(TikzComponent::Resistor, "R1", [64., 0.], [128., 0.]),
+            bipole(TikzComponent::Resistor, "R2", [32., -32.], [32., -96.]),
+            bipole(TikzComponent::Resistor, "R3", [-64., 0.], [0., 0.]),
+        ];
+        let netlist = Netlist::build(&records);
+        let net = netlist.resolve("R1.start").unwrap();
+        assert_eq!(netlist.resolve("R2.start"), Some(net));
+        assert_eq!(netlist.resolve("R3.end"), Some(net));
+        assert_ne!(netlist.resolve("R1.end"), Some(net));
+        assert_eq!(
+            netlist.to_string(),
+            "N1: R1.start R2.start R3.end\nN2: R1.end\nN3: R2.end\nN4: R3.start\n"
+        );
+
+        // Wires are left out of the components, which keep the index of their record.
+        let indexes: Vec<usize> = netlist.components.iter().map(|c| c.index).collect();
+        assert_eq!(indexes, [2, 3, 4]);
+    }
+
+    #[test]
+    fn distance_to_a_segment() {
+        let (a, b) = (Vec2::ZERO, Vec2::new(4., 0.));
+        assert_eq!(segment_distance(Vec2::new(2., 3.), a, b), 3.);
+        assert_eq!(segment_distance(Vec2::new(7., 4.), a, b), 5.);
+        // A wire of no length is the point it sits on.
+        assert_eq!(segment_distance(Vec2::new(3., 4.), a, a), 5.);
+        let at = |x, y| Position { x, y };
+        assert!(!inside_segment(at(0, 0), at(0, 0), at(0, 0)));
+        assert!(inside_segment(at(1, 1), at(0, 0), at(2, 2)));
+        assert!(!inside_segment(at(1, 2), at(0, 0), at(2, 2)));
+    }
+
+    #[test]
+    fn crossing_wires_stay_apart() {
+        let records = [wire([0., 0.], [64., 0.]), wire([32., 32.], [32., -32.])];
+        let netlist = Netlist::build(&records);
+        assert_eq!(netlist.nets.len(), 2);
+        assert_ne!(
+            netlist.net_at(Position { x: 0, y: 0 }),
+            netlist.net_at(Position { x: 32, y: 32 })
+        );
+    }
+
+    #[test]
+    fn grounds_are_one_net() {
+        let records = [
+            ground([0., 0.]),
+            bipole(TikzComponent::Resistor, "R1", [0., 0.], [0., 64.]),
+            ground([96., 0.]),
+            bipole(TikzComponent::Resistor, "R2", [96., 0.], [96., 64.]),
+        ];
+        let netlist = Netlist::build(&records);
+        let names: Vec<&str> = netlist.nets.iter().map(|net| net.name.as_str()).collect();
+        assert_eq!(names, ["0", "N1", "N2"]);
+        assert_eq!(netlist.resolve("R1.start"), netlist.ground());
+        assert_eq!(netlist.resolve("R2.start"), netlist.ground());
+        assert_eq!(netlist.resolve("0"), netlist.ground());
+    }
+
+    #[test]
+    fn pins_of_nodes_are_terminals() {
+        let records = [
+            node(TikzComponent::AndGate, "AND1", [0., 0.]),
+            // The output pin is two grid squares to the right of the gate.
+            wire([32., 0.], [96., 0.]),
+            bipole(TikzComponent::Resistor, "R1", [96., 0.], [160., 0.]),
+        ];
+        let netlist = Netlist::build(&records);
+        let gate = &netlist.components[0];
+        let pins: Vec<&str> = gate.terminals.iter().map(|terminal| terminal.pin).collect();
+        assert_eq!(pins, ["in 1", "in 2", "out"]);
+        assert!(netlist.resolve("AND1.out").is_some());
+        assert_eq!(netlist.resolve("AND1.out"), netlist.resolve("R1.start"));
+        assert_eq!(netlist.resolve("AND1.missing"), None);
+    }
+}
@@ -0,0 +1,286 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::graph::netlist::segment_distance;
+use crate::project::ComponentRecord;
+use crate::structs::{ComponentStructure, TikzComponent};
+use crate::GRID_SIZE;
+
+/// Extra cost of a corner, in grid squares, so a route with fewer corners wins over a slightly
+/// shorter one.
+const TURN: u32 = 4;
+/// Grid squares a route may go past the circuit.
+const MARGIN: i32 = 3;
+
+const DIRECTIONS: [IVec2; 4] = [IVec2::X, IVec2::Y, IVec2::NEG_X, IVec2::NEG_Y];
+/// Direction of the first step of a route, which turns nowhere.
+const NO_DIRECTION: usize = DIRECTIONS.len();
+
+/// Grid square of a point, or `None` when the point is off the grid.
+fn square(point: Vec2) -> Option<IVec2> {
+    let scaled = point / GRID_SIZE;
+    let rounded = scaled.round();
+    (scaled.distance(rounded) < 1e-3).then(|| rounded.as_ivec2())
+}
+
+/// Grid squares around a point: itself when it is on the grid, the corners of the square it is in
+/// otherwise, so no route goes over it.
+fn squares_around(point: Vec2) -> Vec<IVec2> {
+    let scaled = point / GRID_SIZE;
+    let (min, max) = (scaled.floor().as_ivec2(), scaled.ceil().as_ivec2());
+    let mut squares = vec![min, max, IVec2::new(min.x, max.y), IVec2::new(max.x, min.y)];
+    squares.dedup();
+    squares
+}
+
+/// What is already drawn, as seen by the router.
+#[derive(Default)]
+struct Obstacles {
+    /// Squares no route goes through: terminals, the bodies of the components and crossings.
+    blocked: HashSet<IVec2>,
+    /// Squares inside a wire, with whether the wire is horizontal. A route may only cross them.
+    wires: HashMap<IVec2, bool>,
+    min: IVec2,
+    max: IVec2,
+}
+
+impl Obstacles {
+    fn new(records: &[ComponentRecord]) -> Self {
+        let mut obstacles = Self {
+            min: IVec2::MAX,
+            max: IVec2::MIN,
+            ..default()
+        };
+        for record in records {
+            for terminal in record.terminals() {
+                for square in squares_around(terminal) {
+                    obstacles.blocked.insert(square);
+                    obstacles.min = obstacles.min.min(square);
+                    obstacles.max = obstacles.max.max(square);
+                }
+            }
+            match record.structure {
+                ComponentStructure::To([initial, fin]) => {
+                    obstacles.add_segment(record.kind, initial, fin)
+                }
+                ComponentStructure::Node(_) => obstacles.add_body(record),
+            }
+        }
+        obstacles
+    }
+
+    /// Wires are crossed only when they are straight and on the grid. The other segments block
+    /// every square they go near.
+    fn add_segment(&mut self, kind: TikzComponent, initial: Vec2, fin: Vec2) {
+        if let (TikzComponent::Line, Some(a), Some(b)) = (kind, square(initial), square(fin)) {
+            if a.x == b.x || a.y == b.y {
+                let horizontal = a.y == b.y;
+                let step = (b - a).signum();
+                let mut current = a + step;
+                while current != b {
+                    if self.wires.insert(current, horizontal) == Some(!horizontal) {
+                        self.blocked.insert(current);
+                    }
+                    current += step;
+                }
+                return;
+            }
+        }
+
+        let (a, b) = (initial / GRID_SIZE, fin / GRID_SIZE);
+        let (min, max) = (a.min(b).floor().as_ivec2(), a.max(b).ceil().as_ivec2());
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                if segment_distance(Vec2::new(x as f32, y as f32), a, b) < 0.75 {
+                    self.blocked.insert(IVec2::new(x, y));
+                }
+            }
+        }
+    }
+
+    /// Blocks the box around the pins of a node. A box that is flat, like the one of the NOT gate,
+    /// gets a square on each side for its body.
+    fn add_body(&mut self, record: &ComponentRecord) {
+        let pins: Vec<Vec2> = record
+            .structure
+            .pins(record.kind, record.build_info.unwrap_or_default())
+            .into_iter()
+            .map(|(_, pin)| pin / GRID_SIZE)
+            .collect();
+        if pins.is_empty() {
+            return;
+        }
+        let min = pins.iter().fold(Vec2::INFINITY, |min, &p| min.min(p));
+        let max = pins.iter().fold(Vec2::NEG_INFINITY, |max, &p| max.max(p));
+        let flat = (max - min).cmplt(Vec2::splat(0.5));
+        let grow = Vec2::select(flat, Vec2::ONE, Vec2::ZERO);
+        let (min, max) = ((min - grow).floor().as_ivec2(), (max + grow).ceil().as_ivec2());
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                self.blocked.insert(IVec2::new(x, y));
+            }
+        }
+    }
+}
+
+/// Orthogonal path on the grid from `from` to `to` that goes around the components and only
+/// crosses the wires, preferring few corners. Returns the points where it starts, turns and
+/// ends, or `None` when the ends are off the grid or no path exists.
+pub fn route(records: &[ComponentRecord], from: Vec2, to: Vec2) -> Option<Vec<Vec2>> {
+    let (start, end) = (square(from)?, square(to)?);
+    if start == end {
+        return None;
+    }
+    let obstacles = Obstacles::new(records);
+    let min = obstacles.min.min(start).min(end) - MARGIN;
+    let max = obstacles.max.max(start).max(end) + MARGIN;
+
+    let mut costs: HashMap<(IVec2, usize), u32> = HashMap::default();
+    let mut previous: HashMap<(IVec2, usize), (IVec2, usize)> = HashMap::default();
+    let mut queue = BinaryHeap::new();
+    costs.insert((start, NO_DIRECTION), 0);
+    queue.push(Reverse((0, start.x, start.y, NO_DIRECTION)));
+
+    let last = loop {
+        let Reverse((cost, x, y, direction)) = queue.pop()?;
+        let current = IVec2::new(x, y);
+        if current == end {
+            break (current, direction);
+        }
+        if costs
+            .get(&(current, direction))
+            .is_some_and(|&best| best < cost)
+        {
+            continue;
+        }
+        for (next_direction, step) in DIRECTIONS.iter().enumerate() {
+            let turns = direction != NO_DIRECTION && next_direction != direction;
+            // Going back, or turning on a wire, which would connect to it.
+            if turns && (*step == -DIRECTIONS[direction] || obstacles.wires.contains_key(&current))
+            {
+                continue;
+            }
+            let next = current + *step;
+            let crosses = |horizontal: &bool| *horizontal != (step.y == 0);
+            if next.cmplt(min).any() || next.cmpgt(max).any() {
+                continue;
+            }
+            if next != end
+                && (obstacles.blocked.contains(&next)
+                    || obstacles.wires.get(&next).is_some_and(|h| !crosses(h)))
+            {
+                continue;
+            }
+            let next_cost = cost + 1 + if turns { TURN } else { 0 };
+            let state = (next, next_direction);
+            if costs.get(&state).is_none_or(|&best| next_cost < best) {
+                costs.insert(state, next_cost);
+                previous.insert(state, (current, direction));
+                queue.push(Reverse((next_cost, next.x, next.y, next_direction)));
+            }
+        }
+    };
+
+    let mut states = vec![last];
+    while let Some(&state) = previous.get(states.last().unwrap()) {
+        states.push(state);
+    }
+    states.reverse();
+    let mut points = vec![from];
+    for pair in states.windows(2).skip(1) {
+        if pair[0].1 != pair[1].1 {
+            points.push(pair[0].0.as_vec2() * GRID_SIZE);
+        }
+    }
+    points.push(to);
+    Some(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::fixtures::{node, wire};
+
+    fn at(x: i32, y: i32) -> Vec2 {
+        IVec2::new(x, y).as_vec2() * GRID_SIZE
+    }
+
+    /// Every grid square the route goes through, ends and corners included.
+    fn squares(points: &[Vec2]) -> Vec<IVec2> {
+        let mut squares = vec![square(points[0]).unwrap()];
+        for pair in points.windows(2) {
+            let (a, b) = (square(pair[0]).unwrap(), square(pair[1]).unwrap());
+            assert!(a.x == b.x || a.y == b.y, "{a} to {b} is not straight");
+            let step = (b - a).signum();
+            let mut current = a;
+            while current != b {
+                current += step;
+                squares.push(current);
+            }
+        }
+        squares
+    }
+
+    #[test]
+    fn goes_around_a_body() {
+        // The pins of the gate are two squares to each side, so its body spans x -2..=2.
+        let records = [node(TikzComponent::AndGate, "", at(0, 0).into())];
+        let points = route(&records, at(-5, 0), at(5, 0)).unwrap();
+        assert_eq!(points.len(), 4, "{points:?}");
+        assert!(squares(&points)
+            .iter()
+            .all(|square| square.x.abs() > 2 || square.y.abs() > 1));
+    }
+
+    #[test]
+    fn crosses_wires() {
+        let records = [wire(at(0, -10).into(), at(0, 10).into())];
+        assert_eq!(route(&records, at(-3, 2), at(3, 2)), Some(vec![at(-3, 2), at(3, 2)]));
+    }
+
+    #[test]
+    fn never_turns_on_a_wire() {
+        // The wire is in the way of the straight route, which has to go around it instead of
+        // running along it.
+        let records = [wire(at(-10, 0).into(), at(10, 0).into())];
+        let points = route(&records, at(-12, 0), at(12, 0)).unwrap();
+        let squares = squares(&points);
+        assert!(
+            squares[1..squares.len() - 1]
+                .iter()
+                .all(|square| square.y != 0),
+            "{points:?}"
+        );
+
+        // Turning where the wire is would connect to it, so the corner is off it.
+        for to in [at(4, 3), at(-4, 3)] {
+            let points = route(&records, at(0, -3), to).unwrap();
+            assert_eq!(points.len(), 3, "{points:?}");
+            assert_ne!(points[1].y, 0.);
+        }
+    }
+
+    #[test]
+    fn needs_grid_ends() {
+        assert_eq!(square(Vec2::new(24., 0.)), None);
+        assert_eq!(square(Vec2::new(32.01, -16.)), Some(IVec2::new(2, -1)));
+        assert_eq!(route(&[], Vec2::new(24., 0.), at(4, 4)), None);
+        assert_eq!(route(&[], at(0, 0), Vec2::new(64., 40.)), None);
+        assert_eq!(route(&[], at(1, 1), at(1, 1)), None);
+    }
+
+    #[test]
+    fn prefers_fewer_corners() {
+        // Every staircase from one end to the other is as long, but the one with a single corner
+        // wins.
+        let points = route(&[], at(0, 0), at(5, 3)).unwrap();
+        assert_eq!(points.len(), 3, "{points:?}");
+        assert!(points[1] == at(5, 0) || points[1] == at(0, 3), "{points:?}");
+        assert_eq!(route(&[], at(0, 0), at(0, 6)), Some(vec![at(0, 0), at(0, 6)]));
+    }
+}
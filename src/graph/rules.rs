@@ -0,0 +1,249 @@
+use crate::graph::netlist::{inside_segment, terminals, Netlist};
+use crate::project::ComponentRecord;
+use crate::structs::{ComponentStructure, Position, TikzComponent};
+
+/// A problem found by [`check`], on the component with its index in the records, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub index: Option<usize>,
+    pub kind: IssueKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IssueKind {
+    /// A wire end that touches nothing.
+    DanglingWire(Position),
+    /// A gate input with no other component on its net, with the name of the pin.
+    FloatingInput(&'static str),
+    /// A voltage source with both ends on the same net.
+    ShortedSource,
+    NoGround,
+    /// A component drawn over another one, with the label of the first.
+    Duplicate(String),
+    /// A bipole that starts where it ends.
+    ZeroLength,
+}
+
+impl Issue {
+    /// Message shown in the panel, with the label of the component.
+    pub fn message(&self, records: &[ComponentRecord]) -> String {
+        let label = self
+            .index
+            .map(|index| records[index].label.as_str())
+            .unwrap_or_default();
+        match &self.kind {
+            IssueKind::DanglingWire(pos) => {
+                let pos = pos.tikz_coords();
+                format!("Fio solto em ({}, {})", pos.x, pos.y)
+            }
+            IssueKind::FloatingInput(pin) => format!("Entrada {label}.{pin} sem conexão"),
+            IssueKind::ShortedSource => format!("Fonte {label} em curto"),
+            IssueKind::NoGround => "Circuito sem terra".to_string(),
+            IssueKind::Duplicate(other) => format!("{label} sobreposto a {other}"),
+            IssueKind::ZeroLength => format!("{label} com comprimento zero"),
+        }
+    }
+}
+
+/// If two components are drawn at the same place, taking bipoles drawn backwards too.
+fn same_place(a: ComponentStructure, b: ComponentStructure) -> bool {
+    let ends = |structure| match structure {
+        ComponentStructure::To([initial, fin]) => {
+            let (initial, fin) = (Position::from(initial), Position::from(fin));
+            [initial.min(fin), initial.max(fin)]
+        }
+        ComponentStructure::Node(pos) => [pos.into(); 2],
+    };
+    matches!(
+        (a, b),
+        (ComponentStructure::To(_), ComponentStructure::To(_))
+            | (ComponentStructure::Node(_), ComponentStructure::Node(_))
+    ) && ends(a) == ends(b)
+}
+
+/// Checks the electrical rules of a circuit: wires and gate inputs left unconnected, shorted
+/// voltage sources, a missing ground, components drawn twice and bipoles without length. Logic
+/// circuits, with only gates, need no ground.
+pub fn check(records: &[ComponentRecord]) -> Vec<Issue> {
+    let netlist = Netlist::build(records);
+    let mut issues = Vec::new();
+    let mut issue = |index, kind| issues.push(Issue { index, kind });
+
+    let wires: Vec<(usize, [Position; 2])> = records
+        .iter()
+        .enumerate()
+        .filter_map(|(i, record)| match (record.kind, record.structure) {
+            (TikzComponent::Line, ComponentStructure::To([initial, fin])) => {
+                Some((i, [initial.into(), fin.into()]))
+            }
+            _ => None,
+        })
+        .collect();
+    for &(i, ends) in &wires {
+        for end in ends {
+            let touches_terminal = records
+                .iter()
+                .enumerate()
+                .any(|(j, record)| j != i && terminals(record).iter().any(|&(_, pos)| pos == end));
+            let touches_wire = wires
+                .iter()
+                .any(|&(j, [a, b])| j != i && inside_segment(end, a, b));
+            if !touches_terminal && !touches_wire && ends[0] != ends[1] {
+                issue(Some(i), IssueKind::DanglingWire(end));
+            }
+        }
+    }
+
+    for component in &netlist.components {
+        let kind = component.record.kind;
+        if kind.is_gate() {
+            for terminal in &component.terminals {
+                let alone = netlist.terminals_on(terminal.net).count() == 1;
+                if terminal.pin.starts_with("in") && alone {
+                    issue(Some(component.index), IssueKind::FloatingInput(terminal.pin));
+                }
+            }
+        }
+        let is_source = matches!(
+            kind,
+            TikzComponent::VSource
+                | TikzComponent::Battery
+                | TikzComponent::SinVSource
+                | TikzComponent::ControlledVSource
+        );
+        if let ([start, end], true) = (component.terminals.as_slice(), is_source) {
+            if start.net == end.net && start.position != end.position {
+                issue(Some(component.index), IssueKind::ShortedSource);
+            }
+        }
+    }
+
+    let needs_ground = netlist
+        .components
+        .iter()
+        .any(|component| !component.record.kind.is_gate());
+    if needs_ground && netlist.ground().is_none() {
+        issue(None, IssueKind::NoGround);
+    }
+
+    for (i, record) in records.iter().enumerate() {
+        if record.kind == TikzComponent::Dot {
+            continue;
+        }
+        if let Some(other) = records[..i].iter().find(|other| {
+            other.kind != TikzComponent::Dot && same_place(other.structure, record.structure)
+        }) {
+            issue(Some(i), IssueKind::Duplicate(other.label.clone()));
+        }
+        if let ComponentStructure::To([initial, fin]) = record.structure {
+            if Position::from(initial) == Position::from(fin) {
+                issue(Some(i), IssueKind::ZeroLength);
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::fixtures::{self, bipole, node, wire};
+
+    /// A source across two resistors, grounded.
+    fn circuit() -> Vec<ComponentRecord> {
+        use TikzComponent::*;
+        fixtures::divider((VSource, 10.), (Resistor, 1e3), (Resistor, 2e3))
+    }
+
+    fn kinds(records: &[ComponentRecord]) -> Vec<(Option<usize>, IssueKind)> {
+        check(records)
+            .into_iter()
+            .map(|issue| (issue.index, issue.kind))
+            .collect()
+    }
+
+    #[test]
+    fn clean_circuit() {
+        assert_eq!(kinds(&circuit()), []);
+    }
+
+    #[test]
+    fn dangling_wire() {
+        let mut records = circuit();
+        records.extend([
+            wire([0., 64.], [0., 128.]),
+            wire([0., 128.], [64., 128.]),
+            wire([64., 128.], [64., 64.]),
+        ]);
+        // Starting on the middle of another wire is a connection.
+        records.push(wire([32., 128.], [32., 112.]));
+        assert_eq!(
+            kinds(&records),
+            [(Some(8), IssueKind::DanglingWire(Position { x: 32, y: 112 }))]
+        );
+    }
+
+    #[test]
+    fn floating_input() {
+        let records = [
+            node(TikzComponent::AndGate, "AND1", [0., 0.]),
+            // Their outputs are on the inputs of the first gate, and the inputs between them
+            // are on one net, so only the outer ones float.
+            node(TikzComponent::AndGate, "AND2", [-64., 16.]),
+            node(TikzComponent::OrGate, "OR1", [-64., -16.]),
+        ];
+        assert_eq!(
+            kinds(&records),
+            [
+                (Some(1), IssueKind::FloatingInput("in 1")),
+                (Some(2), IssueKind::FloatingInput("in 2")),
+            ]
+        );
+        assert_eq!(check(&records)[0].message(&records), "Entrada AND2.in 1 sem conexão");
+    }
+
+    #[test]
+    fn shorted_source() {
+        let mut records = circuit();
+        records.extend([wire([0., 64.], [-32., 64.]), wire([-32., 64.], [-32., 0.])]);
+        records.push(wire([-32., 0.], [0., 0.]));
+        assert_eq!(kinds(&records), [(Some(1), IssueKind::ShortedSource)]);
+        // A resistor across the same net is no short.
+        let mut records = circuit();
+        records.push(bipole(TikzComponent::Resistor, "E3", [0., 64.], [64., 64.]));
+        assert!(!kinds(&records)
+            .iter()
+            .any(|(_, kind)| *kind == IssueKind::ShortedSource));
+    }
+
+    #[test]
+    fn no_ground() {
+        let mut records = circuit();
+        records.retain(|record| record.kind != TikzComponent::Ground);
+        assert_eq!(kinds(&records), [(None, IssueKind::NoGround)]);
+        assert_eq!(check(&records)[0].message(&records), "Circuito sem terra");
+        // Gates alone need none.
+        let records = [node(TikzComponent::NotGate, "NOT1", [0., 0.])];
+        assert_eq!(kinds(&records), [(Some(0), IssueKind::FloatingInput("in"))]);
+    }
+
+    #[test]
+    fn duplicate() {
+        let mut records = circuit();
+        records.push(bipole(TikzComponent::Resistor, "E3", [64., 0.], [64., 64.]));
+        assert_eq!(kinds(&records), [(Some(5), IssueKind::Duplicate("Z2".to_string()))]);
+        // Dots sit on the ends of other components.
+        let mut records = circuit();
+        records.push(node(TikzComponent::Dot, "", [64., 64.]));
+        assert_eq!(kinds(&records), []);
+    }
+
+    #[test]
+    fn zero_length() {
+        let mut records = circuit();
+        records.push(bipole(TikzComponent::Capacitor, "E3", [64., 64.], [64., 64.]));
+        assert_eq!(kinds(&records), [(Some(5), IssueKind::ZeroLength)]);
+        assert_eq!(check(&records)[0].message(&records), "E3 com comprimento zero");
+    }
+}
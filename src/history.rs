@@ -0,0 +1,196 @@
+use std::collections::VecDeque;
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::actions::select_node::{ctrl_pressed, shift_pressed, Selected};
+use crate::project::{records, ComponentRecord, RecordQuery, ReplaceCircuit};
+use crate::structs::Anchored;
+
+pub struct HistoryPlugin;
+
+impl Plugin for HistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(History::default())
+            .add_observer(checkpoint)
+            .add_observer(undo)
+            .add_observer(redo)
+            .add_systems(
+                Update,
+                (history_shortcuts, checkpoint_move.run_if(input_just_pressed(MouseButton::Right))),
+            );
+    }
+}
+
+/// Snapshots kept for undo. The oldest are dropped past it.
+pub const HISTORY_LIMIT: usize = 100;
+
+/// Snapshots of the whole circuit, taken before each edit.
+///
+/// Going back to a snapshot draws the circuit again from its records, so the components come
+/// back as new entities with new graph edges, and nothing stays selected.
+#[derive(Resource, Default)]
+pub struct History {
+    undo: VecDeque<Vec<ComponentRecord>>,
+    redo: Vec<Vec<ComponentRecord>>,
+}
+
+impl History {
+    fn push_undo(&mut self, snapshot: Vec<ComponentRecord>) {
+        if self.undo.len() == HISTORY_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(snapshot);
+    }
+
+    /// Saves `current` before an edit, which makes the undone snapshots unreachable.
+    fn checkpoint(&mut self, current: Vec<ComponentRecord>) {
+        if self.undo.back() != Some(&current) {
+            self.push_undo(current);
+        }
+        self.redo.clear();
+    }
+
+    /// Snapshot to draw in place of `current`, which is kept to be redone.
+    fn undo(&mut self, current: Vec<ComponentRecord>) -> Option<Vec<ComponentRecord>> {
+        // Snapshots equal to the current circuit come from edits that changed nothing.
+        while let Some(previous) = self.undo.pop_back() {
+            if previous != current {
+                self.redo.push(current);
+                return Some(previous);
+            }
+        }
+        None
+    }
+
+    /// Snapshot last undone, to draw in place of `current`.
+    fn redo(&mut self, current: Vec<ComponentRecord>) -> Option<Vec<ComponentRecord>> {
+        let next = self.redo.pop()?;
+        self.push_undo(current);
+        Some(next)
+    }
+}
+
+/// Saves the current circuit in the history. Trigger it before the commands that edit the
+/// circuit, so the snapshot is taken before they are applied.
+#[derive(Event)]
+pub struct Checkpoint;
+
+#[derive(Event)]
+pub struct Undo;
+
+#[derive(Event)]
+pub struct Redo;
+
+fn checkpoint(_: Trigger<Checkpoint>, mut history: ResMut<History>, components: RecordQuery) {
+    history.checkpoint(records(&components));
+}
+
+/// Draws `snapshot` in place of the circuit. The selection is cleared first, since the selected
+/// entities are about to be replaced.
+fn restore(
+    commands: &mut Commands, selected: &Query<Entity, With<Selected>>,
+    snapshot: Vec<ComponentRecord>,
+) {
+    for entity in selected {
+        commands.entity(entity).remove::<Selected>();
+    }
+    commands.trigger(ReplaceCircuit(snapshot));
+}
+
+fn undo(
+    _: Trigger<Undo>, mut commands: Commands, mut history: ResMut<History>, components: RecordQuery,
+    selected: Query<Entity, With<Selected>>,
+) {
+    if let Some(previous) = history.undo(records(&components)) {
+        restore(&mut commands, &selected, previous);
+    }
+}
+
+fn redo(
+    _: Trigger<Redo>, mut commands: Commands, mut history: ResMut<History>, components: RecordQuery,
+    selected: Query<Entity, With<Selected>>,
+) {
+    if let Some(next) = history.redo(records(&components)) {
+        restore(&mut commands, &selected, next);
+    }
+}
+
+fn history_shortcuts(
+    mut commands: Commands, keys: Res<ButtonInput<KeyCode>>,
+    focused: Res<crate::ui::FocusedInputText>,
+) {
+    if focused.0 != Entity::PLACEHOLDER
+        || !ctrl_pressed(&keys)
+        || !keys.just_pressed(KeyCode::KeyZ)
+    {
+        return;
+    }
+
+    if shift_pressed(&keys) {
+        commands.trigger(Redo);
+    } else {
+        commands.trigger(Undo);
+    }
+}
+
+fn checkpoint_move(mut commands: Commands, moving: Query<(), (With<Selected>, With<Anchored>)>) {
+    if !moving.is_empty() {
+        commands.trigger(Checkpoint);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::{ComponentStructure, Info, TikzComponent};
+
+    /// A circuit of `count` wires.
+    fn circuit(count: usize) -> Vec<ComponentRecord> {
+        (0..count)
+            .map(|i| ComponentRecord {
+                kind: TikzComponent::Line,
+                structure: ComponentStructure::To([Vec2::ZERO, Vec2::new(16. * i as f32, 16.)]),
+                build_info: None,
+                info: Info::default(),
+                label: String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn oldest_snapshot_is_dropped() {
+        let mut history = History::default();
+        for count in 0..=HISTORY_LIMIT {
+            history.checkpoint(circuit(count));
+        }
+        assert_eq!(history.undo.len(), HISTORY_LIMIT);
+        assert_eq!(history.undo.front(), Some(&circuit(1)));
+        assert_eq!(history.undo.back(), Some(&circuit(HISTORY_LIMIT)));
+    }
+
+    #[test]
+    fn checkpoint_clears_redo() {
+        let mut history = History::default();
+        history.checkpoint(circuit(0));
+        assert_eq!(history.undo(circuit(1)), Some(circuit(0)));
+        assert_eq!(history.redo.len(), 1);
+        history.checkpoint(circuit(0));
+        assert!(history.redo.is_empty());
+        assert_eq!(history.redo(circuit(2)), None);
+    }
+
+    #[test]
+    fn undo_then_redo_gives_the_circuit_back() {
+        let mut history = History::default();
+        history.checkpoint(circuit(0));
+        history.checkpoint(circuit(1));
+        history.checkpoint(circuit(2));
+        // The last snapshot is the circuit as it is, so it is skipped.
+        assert_eq!(history.undo(circuit(2)), Some(circuit(1)));
+        assert_eq!(history.undo(circuit(1)), Some(circuit(0)));
+        assert_eq!(history.undo(circuit(0)), None);
+        assert_eq!(history.redo(circuit(0)), Some(circuit(1)));
+        assert_eq!(history.redo(circuit(1)), Some(circuit(2)));
+        assert_eq!(history.redo(circuit(2)), None);
+    }
+}
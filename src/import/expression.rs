@@ -0,0 +1,458 @@
+use bevy::{
+    math::{IVec2, Vec2},
+    utils::HashMap,
+};
+
+use crate::project::ComponentRecord;
+use crate::structs::{ComponentStructure, Info, TikzComponent, TIKZ_ORIGIN};
+use crate::GRID_SIZE;
+
+/// Canvas point where the rail of the first input starts, (0, 0) in the exported figure.
+const ORIGIN: Vec2 = TIKZ_ORIGIN;
+/// Distance between the rails of the inputs, in grid units.
+const RAIL: i32 = 2;
+/// Distance between the rows of two operands, in grid units. A gate fits between them.
+const ROW: i32 = 2;
+/// Distance between the centers of the gates of two levels, in grid units.
+const COLUMN: i32 = 8;
+/// Half the width of a gate, from its center to its pins, in grid units.
+const HALF: i32 = 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionError {
+    Empty,
+    /// A character that does not fit where it is, with its position in the text.
+    Unexpected(char, usize),
+    MissingOperand,
+    UnclosedParenthesis,
+    /// The expression is a single input, which needs no gate.
+    NoGates,
+}
+
+impl std::fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Empty expression"),
+            Self::Unexpected(c, i) => write!(f, "Unexpected {c:?} at {i}"),
+            Self::MissingOperand => write!(f, "Missing operand at the end of the expression"),
+            Self::UnclosedParenthesis => write!(f, "Unclosed parenthesis"),
+            Self::NoGates => write!(f, "The expression needs no gate"),
+        }
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// A Boolean expression, with the operators as the gates that compute them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expression {
+    Input(String),
+    Not(Box<Expression>),
+    Gate(TikzComponent, Box<Expression>, Box<Expression>),
+}
+
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+}
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Option<(usize, char)> {
+        while let Some(&(_, c)) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+        self.chars.peek().copied()
+    }
+
+    /// Operands joined by operators of the given precedence, from `|` to `&`, grouped to the left.
+    fn binary(&mut self, level: usize) -> Result<Expression, ExpressionError> {
+        const LEVELS: [(&[char], TikzComponent); 3] = [
+            (&['|', '+'], TikzComponent::OrGate),
+            (&['^'], TikzComponent::XorGate),
+            (&['&', '*', '·'], TikzComponent::AndGate),
+        ];
+        let Some(&(operators, kind)) = LEVELS.get(level) else {
+            return self.unary();
+        };
+        let mut left = self.binary(level + 1)?;
+        while self.peek().is_some_and(|(_, c)| operators.contains(&c)) {
+            self.chars.next();
+            let right = self.binary(level + 1)?;
+            left = Expression::Gate(kind, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    /// An operand, negated by a `!` or `~` before it or a `'` after it.
+    fn unary(&mut self) -> Result<Expression, ExpressionError> {
+        let mut operand = match self.peek() {
+            None => return Err(ExpressionError::MissingOperand),
+            Some((_, '!' | '~' | '¬')) => {
+                self.chars.next();
+                return Ok(Expression::Not(Box::new(self.unary()?)));
+            }
+            Some((_, '(')) => {
+                self.chars.next();
+                let inner = self.binary(0)?;
+                match self.peek() {
+                    Some((_, ')')) => self.chars.next(),
+                    Some((i, c)) => return Err(ExpressionError::Unexpected(c, i)),
+                    None => return Err(ExpressionError::UnclosedParenthesis),
+                };
+                inner
+            }
+            Some((_, c)) if c.is_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while let Some(&(_, c)) = self.chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    name.push(c);
+                    self.chars.next();
+                }
+                Expression::Input(name)
+            }
+            Some((i, c)) => return Err(ExpressionError::Unexpected(c, i)),
+        };
+        while let Some((_, '\'')) = self.peek() {
+            self.chars.next();
+            operand = Expression::Not(Box::new(operand));
+        }
+        Ok(operand)
+    }
+}
+
+impl Expression {
+    /// Reads an expression like `(A & !B) | (B ^ C)`. `!`, `~` and a trailing `'` negate, `&` and
+    /// `*` are AND, `^` is XOR and `|` and `+` are OR, from the tightest to the loosest.
+    pub fn parse(text: &str) -> Result<Self, ExpressionError> {
+        let mut parser = Parser {
+            chars: text.char_indices().peekable(),
+        };
+        if parser.peek().is_none() {
+            return Err(ExpressionError::Empty);
+        }
+        let expression = parser.binary(0)?;
+        match parser.peek() {
+            None => Ok(expression),
+            Some((i, c)) => Err(ExpressionError::Unexpected(c, i)),
+        }
+    }
+
+    /// Inputs the expression reads, sorted and without repetitions.
+    pub fn inputs(&self) -> Vec<String> {
+        let mut inputs = Vec::new();
+        self.collect_inputs(&mut inputs);
+        inputs.sort();
+        inputs.dedup();
+        inputs
+    }
+
+    fn collect_inputs(&self, inputs: &mut Vec<String>) {
+        match self {
+            Self::Input(name) => inputs.push(name.clone()),
+            Self::Not(inner) => inner.collect_inputs(inputs),
+            Self::Gate(_, a, b) => {
+                a.collect_inputs(inputs);
+                b.collect_inputs(inputs);
+            }
+        }
+    }
+
+    /// Gates on the longest path from an input to the output.
+    fn levels(&self) -> i32 {
+        match self {
+            Self::Input(_) => 0,
+            Self::Not(inner) => 1 + inner.levels(),
+            Self::Gate(_, a, b) => 1 + a.levels().max(b.levels()),
+        }
+    }
+}
+
+/// Records of a circuit being placed, in grid units relative to [`ORIGIN`].
+struct Layout {
+    records: Vec<ComponentRecord>,
+    rails: HashMap<String, i32>,
+    /// Rows where each input is read.
+    taps: HashMap<String, Vec<i32>>,
+    next_row: i32,
+    /// Column of the gate computing the whole expression.
+    last_column: i32,
+}
+
+fn point(grid: IVec2) -> Vec2 {
+    ORIGIN + grid.as_vec2() * GRID_SIZE
+}
+
+fn line(start: IVec2, end: IVec2, label: &str) -> ComponentRecord {
+    ComponentRecord::new(
+        TikzComponent::Line,
+        Info::default().with_label(label.to_string()),
+        ComponentStructure::To([point(start), point(end)]),
+    )
+}
+
+impl Layout {
+    /// Wires `from` to the pin at `to`, turning a little before the pin when they are on different
+    /// rows. The gap before a column only has the turns of the wires of its own gate.
+    fn connect(&mut self, from: IVec2, to: IVec2) {
+        if from.y == to.y {
+            self.records.push(line(from, to, ""));
+            return;
+        }
+        let turn = to.x - 1;
+        let corners = [IVec2::new(turn, from.y), IVec2::new(turn, to.y)];
+        self.records.push(line(from, corners[0], ""));
+        self.records.push(line(corners[0], corners[1], ""));
+        self.records.push(line(corners[1], to, ""));
+    }
+
+    fn gate(&mut self, kind: TikzComponent, center: IVec2) {
+        self.records.push(ComponentRecord::new(
+            kind,
+            Info::default(),
+            ComponentStructure::Node(point(center)),
+        ));
+    }
+
+    /// Places the gates of `expression`, `depth` levels before the output, with its operands on
+    /// rows from the next one down. Returns where its value comes out.
+    fn place(&mut self, expression: &Expression, depth: i32) -> IVec2 {
+        let column = self.last_column - depth * COLUMN;
+        match expression {
+            Expression::Input(name) => {
+                let row = self.next_row;
+                self.next_row -= ROW;
+                self.taps.entry(name.clone()).or_default().push(row);
+                IVec2::new(self.rails[name], row)
+            }
+            Expression::Not(inner) => {
+                let input = self.place(inner, depth + 1);
+                let center = IVec2::new(column, input.y);
+                self.gate(TikzComponent::NotGate, center);
+                self.connect(input, center - IVec2::X * HALF);
+                center + IVec2::X * HALF
+            }
+            Expression::Gate(kind, a, b) => {
+                let top = self.place(a, depth + 1);
+                let bottom = self.place(b, depth + 1);
+                // The operands are at least a row apart, so the pins fit between them.
+                let center = IVec2::new(column, (top.y + bottom.y).div_euclid(2));
+                self.gate(*kind, center);
+                self.connect(top, center + IVec2::new(-HALF, 1));
+                self.connect(bottom, center + IVec2::new(-HALF, -1));
+                center + IVec2::X * HALF
+            }
+        }
+    }
+}
+
+/// Draws the gates computing the expression in `text`, with its output called `Y`.
+pub fn parse(text: &str) -> Result<Vec<ComponentRecord>, ExpressionError> {
+    draw(&Expression::parse(text)?, "Y")
+}
+
+/// Draws the gates computing `expression`. Each input has a labeled vertical rail on the left,
+/// every gate level a column to the right of the previous one and each operand a row of its own,
+/// so wires only cross the rails. The output is a wire labeled `output`.
+pub fn draw(
+    expression: &Expression, output: &str,
+) -> Result<Vec<ComponentRecord>, ExpressionError> {
+    let levels = expression.levels();
+    if levels == 0 {
+        return Err(ExpressionError::NoGates);
+    }
+    let inputs = expression.inputs();
+
+    let rails: HashMap<String, i32> = inputs
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.clone(), i as i32 * RAIL))
+        .collect();
+    // The turns before the first gate level, a grid before its pins, stay clear of the last rail.
+    let last_rail = (inputs.len() as i32 - 1) * RAIL;
+    let first_column = last_rail + 2 + 1 + HALF;
+    let mut layout = Layout {
+        records: Vec::new(),
+        rails,
+        taps: HashMap::default(),
+        next_row: 0,
+        last_column: first_column + (levels - 1) * COLUMN,
+    };
+    let end = layout.place(expression, 0);
+    layout
+        .records
+        .push(line(end, end + IVec2::X * HALF, output));
+
+    // Rails start above every row, so no rail end touches a wire of another input.
+    let top = ROW;
+    let mut records = Vec::new();
+    for name in &inputs {
+        let x = layout.rails[name];
+        let mut rows = layout.taps.remove(name).unwrap_or_default();
+        rows.sort_by(|a, b| b.cmp(a));
+        let points: Vec<IVec2> = std::iter::once(top)
+            .chain(rows)
+            .map(|y| IVec2::new(x, y))
+            .collect();
+        for (i, pair) in points.windows(2).enumerate() {
+            records.push(line(pair[0], pair[1], if i == 0 { name } else { "" }));
+        }
+        for &tap in &points[1..points.len() - 1] {
+            records.push(ComponentRecord::new(
+                TikzComponent::Dot,
+                Info::default(),
+                ComponentStructure::Node(point(tap)),
+            ));
+        }
+    }
+    records.extend(layout.records);
+    Ok(records)
+}
+
+/// Draws a lone input, which [`draw`] refuses as it needs no gate: a wire labeled `input` carried
+/// on by one labeled `output`.
+pub fn draw_input(input: &str, output: &str) -> Vec<ComponentRecord> {
+    let middle = IVec2::X * COLUMN;
+    vec![line(IVec2::ZERO, middle, input), line(middle, middle * 2, output)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::netlist::{NetId, Netlist};
+
+    fn input(name: &str) -> Box<Expression> {
+        Box::new(Expression::Input(name.to_string()))
+    }
+
+    fn gate(kind: TikzComponent, a: Box<Expression>, b: Box<Expression>) -> Box<Expression> {
+        Box::new(Expression::Gate(kind, a, b))
+    }
+
+    fn not(inner: Box<Expression>) -> Box<Expression> {
+        Box::new(Expression::Not(inner))
+    }
+
+    #[test]
+    fn precedence() {
+        use TikzComponent::*;
+        let parse = |text| Box::new(Expression::parse(text).unwrap());
+        assert_eq!(parse("A|B&C"), gate(OrGate, input("A"), gate(AndGate, input("B"), input("C"))));
+        assert_eq!(parse("A^B|C"), gate(OrGate, gate(XorGate, input("A"), input("B")), input("C")));
+        assert_eq!(
+            parse("A & B ^ C"),
+            gate(XorGate, gate(AndGate, input("A"), input("B")), input("C"))
+        );
+        // Operators of a level group to the left, and `+`, `*` and `·` are OR and AND too.
+        assert_eq!(parse("A+B+C"), gate(OrGate, gate(OrGate, input("A"), input("B")), input("C")));
+        assert_eq!(parse("A*B·C"), parse("(A&B)&C"));
+    }
+
+    #[test]
+    fn negation() {
+        use TikzComponent::*;
+        let parse = |text| Box::new(Expression::parse(text).unwrap());
+        assert_eq!(parse("A'"), not(input("A")));
+        assert_eq!(parse("A''"), not(not(input("A"))));
+        assert_eq!(parse("!A & B'"), gate(AndGate, not(input("A")), not(input("B"))));
+        assert_eq!(parse("~¬x_1"), not(not(input("x_1"))));
+        // A `'` after parentheses negates all of them.
+        assert_eq!(parse("(A|B)'"), not(gate(OrGate, input("A"), input("B"))));
+        assert_eq!(
+            parse("!(A|B)&C"),
+            gate(AndGate, not(gate(OrGate, input("A"), input("B"))), input("C"))
+        );
+    }
+
+    #[test]
+    fn parentheses() {
+        use TikzComponent::*;
+        let parse = |text| Box::new(Expression::parse(text).unwrap());
+        assert_eq!(
+            parse("(A|B)&C"),
+            gate(AndGate, gate(OrGate, input("A"), input("B")), input("C"))
+        );
+        assert_eq!(parse("((A))"), input("A"));
+        assert_eq!(
+            parse("A^(B^C)"),
+            gate(XorGate, input("A"), gate(XorGate, input("B"), input("C")))
+        );
+        assert_eq!(Expression::parse("(A | B) ^ C").unwrap().inputs(), ["A", "B", "C"]);
+    }
+
+    #[test]
+    fn errors() {
+        use ExpressionError::*;
+        let err = |text| Expression::parse(text).unwrap_err();
+        assert_eq!(err(" "), Empty);
+        assert_eq!(err("A &"), MissingOperand);
+        assert_eq!(err("!"), MissingOperand);
+        assert_eq!(err("(A | B"), UnclosedParenthesis);
+        assert_eq!(err("((A)"), UnclosedParenthesis);
+        // Positions count from the start of the text, spaces included.
+        assert_eq!(err("A | B)"), Unexpected(')', 5));
+        assert_eq!(err("A $ B"), Unexpected('$', 2));
+        assert_eq!(err("(A B)"), Unexpected('B', 3));
+        assert_eq!(err("A & | B"), Unexpected('|', 4));
+        assert_eq!(err("1 & A"), Unexpected('1', 0));
+        assert_eq!(parse("A'"), Ok(draw(&Expression::parse("A'").unwrap(), "Y").unwrap()));
+        assert_eq!(parse("A"), Err(NoGates));
+    }
+
+    /// Net of the rail of `input`, the wire with its name.
+    fn rail(records: &[ComponentRecord], netlist: &Netlist, input: &str) -> NetId {
+        let rail = records
+            .iter()
+            .find(|record| record.kind == TikzComponent::Line && record.info.label == input)
+            .unwrap();
+        let ComponentStructure::To([start, _]) = rail.structure else {
+            unreachable!()
+        };
+        netlist.net_at(start.into()).unwrap()
+    }
+
+    #[test]
+    fn gates_read_their_rails() {
+        let expression = Expression::parse("(A & B) | (A ^ !C)").unwrap();
+        let mut records = draw(&expression, "Y").unwrap();
+        // Numbered like the app, so every gate has a label to find its pins by.
+        let labels = crate::structs::ComponentLabel::numbered(
+            records
+                .iter()
+                .map(|record| (record.kind, record.label.as_str())),
+        );
+        for (record, label) in records.iter_mut().zip(labels) {
+            record.label = label.label;
+        }
+        let netlist = Netlist::build(&records);
+
+        let rails = ["A", "B", "C", "Y"].map(|input| rail(&records, &netlist, input));
+        // No wire joins two rails, nor a rail and the output.
+        for (i, a) in rails.iter().enumerate() {
+            assert!(rails[i + 1..].iter().all(|b| a != b), "{netlist}");
+        }
+        let pin = |reference| netlist.resolve(reference).unwrap();
+        assert_eq!(pin("AND1.in 1"), rails[0]);
+        assert_eq!(pin("AND1.in 2"), rails[1]);
+        assert_eq!(pin("XOR1.in 1"), rails[0]);
+        assert_eq!(pin("NOT1.in"), rails[2]);
+        assert_eq!(pin("XOR1.in 2"), pin("NOT1.out"));
+        assert_eq!(pin("OR1.in 1"), pin("AND1.out"));
+        assert_eq!(pin("OR1.in 2"), pin("XOR1.out"));
+        assert_eq!(pin("OR1.out"), rails[3]);
+
+        // Every pin on a rail is a gate input, as many as the times the expression reads it.
+        for (input, reads) in [("A", 2), ("B", 1), ("C", 1)] {
+            let rail = rail(&records, &netlist, input);
+            let pins: Vec<_> = netlist
+                .terminals_on(rail)
+                .map(|(_, terminal)| terminal.pin)
+                .collect();
+            assert_eq!(pins.len(), reads, "{input}: {pins:?}");
+            assert!(pins.iter().all(|pin| pin.starts_with("in")), "{input}: {pins:?}");
+        }
+    }
+}
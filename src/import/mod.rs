@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+
+#[cfg(feature = "gui")]
+use crate::{history::Checkpoint, project::ReplaceCircuit};
+
+pub mod expression;
+pub mod spice;
+pub mod tikz;
+
+#[cfg(feature = "gui")]
+pub struct ImportPlugin;
+
+#[cfg(feature = "gui")]
+impl Plugin for ImportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(import_tikz)
+            .add_observer(import_spice)
+            .add_observer(import_expression);
+    }
+}
+
+/// Rebuilds the canvas from the `\draw ... ;` blocks of a circuitikz figure.
+#[derive(Event)]
+pub struct ImportTikz {
+    pub text: String,
+}
+
+#[cfg(feature = "gui")]
+fn import_tikz(trigger: Trigger<ImportTikz>, mut commands: Commands) {
+    match tikz::parse(&trigger.event().text) {
+        Ok(records) => {
+            info!("Imported {} components", records.len());
+            commands.trigger(Checkpoint);
+            commands.trigger(ReplaceCircuit(records));
+        }
+        Err(err) => error!("Could not import circuit: {err}"),
+    }
+}
+
+/// Deck opened by the SPICE import, given on the command line. Without one, the deck beside the
+/// project file is opened.
+#[derive(Resource, Default)]
+pub struct SpiceFile(pub Option<String>);
+
+/// Draws the circuit of a SPICE deck in place of the canvas.
+#[derive(Event)]
+pub struct ImportSpice {
+    pub text: String,
+}
+
+#[cfg(feature = "gui")]
+fn import_spice(trigger: Trigger<ImportSpice>, mut commands: Commands) {
+    match spice::parse(&trigger.event().text) {
+        Ok(import) => {
+            for reason in &import.skipped {
+                warn!("Skipped {reason}");
+            }
+            info!("Imported {} components", import.records.len());
+            commands.trigger(Checkpoint);
+            commands.trigger(ReplaceCircuit(import.records));
+        }
+        Err(err) => error!("Could not import netlist: {err}"),
+    }
+}
+
+/// Draws the gates of a Boolean expression in place of the canvas.
+#[derive(Event)]
+pub struct ImportExpression {
+    pub text: String,
+}
+
+#[cfg(feature = "gui")]
+fn import_expression(trigger: Trigger<ImportExpression>, mut commands: Commands) {
+    match expression::parse(&trigger.event().text) {
+        Ok(records) => {
+            info!("Drew {} components", records.len());
+            commands.trigger(Checkpoint);
+            commands.trigger(ReplaceCircuit(records));
+        }
+        Err(err) => error!("Could not read expression: {err}"),
+    }
+}
@@ -0,0 +1,442 @@
+use bevy::{math::Vec2, utils::HashMap};
+
+use crate::project::ComponentRecord;
+use crate::structs::{ComponentStructure, Info, TikzComponent, TIKZ_ORIGIN};
+use crate::GRID_SIZE;
+
+/// Where the first column is drawn, a component length to the right of the origin of the
+/// exported figure.
+const ORIGIN: Vec2 = Vec2::new(TIKZ_ORIGIN.x + LENGTH, TIKZ_ORIGIN.y);
+/// Horizontal distance between the columns of the components.
+const COLUMN: f32 = 4. * GRID_SIZE;
+/// Vertical distance between the wires of two nets. More than a component, so no wire crosses one.
+const ROW: f32 = 6. * GRID_SIZE;
+/// Length of each component.
+const LENGTH: f32 = 4. * GRID_SIZE;
+
+#[derive(Debug)]
+pub enum SpiceError {
+    Empty,
+    MissingNodes(String),
+    InvalidValue(String),
+}
+
+impl std::fmt::Display for SpiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "No component found in the netlist"),
+            Self::MissingNodes(card) => write!(f, "Missing nodes in {card}"),
+            Self::InvalidValue(card) => write!(f, "Invalid value in {card}"),
+        }
+    }
+}
+
+impl std::error::Error for SpiceError {}
+
+/// Drawn schematic of a netlist, with the cards that could not be drawn.
+pub struct SpiceImport {
+    pub records: Vec<ComponentRecord>,
+    pub skipped: Vec<String>,
+}
+
+/// A two terminal card, with the node at the start of the drawn component first.
+struct Card {
+    name: String,
+    kind: TikzComponent,
+    nodes: [String; 2],
+    value: Option<f64>,
+}
+
+/// Reads a SPICE number, like `4.7k`, `10u`, `1meg` or `100nF`. Letters after the suffix are
+/// units, and digits after it are decimals, like `4k7`.
+fn parse_number(text: &str) -> Option<f64> {
+    let text = text.to_lowercase();
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e')))
+        .unwrap_or(text.len());
+    // An `e` right before the suffix would be read as an exponent.
+    let (number, suffix) = match text[..end].parse::<f64>() {
+        Ok(_) => text.split_at(end),
+        Err(_) => text.split_at(end.saturating_sub(1)),
+    };
+    let number: f64 = number.parse().ok()?;
+    let (exponent, length) = if suffix.starts_with("meg") {
+        (6, 3)
+    } else {
+        let exponent = match suffix.chars().next() {
+            Some('f') => -15,
+            Some('p') => -12,
+            Some('n') => -9,
+            Some('u') => -6,
+            Some('m') => -3,
+            Some('k') => 3,
+            Some('g') => 9,
+            Some('t') => 12,
+            _ => 0,
+        };
+        (exponent, 1)
+    };
+    let decimals: String = suffix
+        .get(length..)
+        .unwrap_or_default()
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    let number = if decimals.is_empty() || exponent == 0 || number.fract() != 0. {
+        number
+    } else {
+        format!("{number}.{decimals}").parse().ok()?
+    };
+    Some(number * 10f64.powi(exponent))
+}
+
+/// Values of a source: its DC value, and the amplitude of its AC or `SIN(...)` part. `None` when
+/// it has a form that cannot be drawn, like `PULSE(...)`, or a value that cannot be read.
+fn source_values(rest: &[&str]) -> Option<(Option<f64>, Option<f64>)> {
+    let rest = rest.join(" ").to_uppercase().replace(['(', ')', ','], " ");
+    let mut words = rest.split_whitespace().peekable();
+    let mut dc = None;
+    let mut ac = None;
+    let mut first = true;
+    while let Some(word) = words.next() {
+        match word {
+            "DC" => dc = Some(parse_number(words.next()?)?),
+            "AC" => {
+                ac = Some(parse_number(words.next()?)?);
+                // The phase, which is not drawn.
+                words.next_if(|word| parse_number(word).is_some());
+            }
+            // SIN(offset amplitude frequency delay damping phase)
+            "SIN" => {
+                let mut numbers = Vec::new();
+                while let Some(number) = words.peek().and_then(|word| parse_number(word)) {
+                    numbers.push(number);
+                    words.next();
+                }
+                ac = Some(*numbers.get(1)?);
+            }
+            word if first => dc = Some(parse_number(word)?),
+            _ => return None,
+        }
+        first = false;
+    }
+    Some((dc, ac))
+}
+
+/// Reads a card, or why it cannot be drawn.
+fn parse_card(line: &str) -> Result<Result<Card, String>, SpiceError> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let [name, n1, n2, rest @ ..] = words.as_slice() else {
+        return Err(SpiceError::MissingNodes(line.to_string()));
+    };
+    let nodes = |first: &str, second: &str| [first.to_string(), second.to_string()];
+    let unsupported = format!("{line} has a source form that cannot be drawn");
+    let value = || {
+        rest.first()
+            .and_then(|word| parse_number(word))
+            .ok_or_else(|| SpiceError::InvalidValue(line.to_string()))
+    };
+
+    let (kind, nodes, value) = match name.chars().next().map(|c| c.to_ascii_uppercase()) {
+        Some('R') => (TikzComponent::Resistor, nodes(n1, n2), Some(value()?)),
+        Some('C') => (TikzComponent::Capacitor, nodes(n1, n2), Some(value()?)),
+        Some('L') => (TikzComponent::Inductor, nodes(n1, n2), Some(value()?)),
+        Some('D') => (TikzComponent::Diode, nodes(n1, n2), None),
+        // Voltage sources are drawn with the positive node at the end.
+        Some('V') => match source_values(rest) {
+            Some((dc, Some(ac))) if dc.unwrap_or(0.) == 0. => {
+                (TikzComponent::SinVSource, nodes(n2, n1), Some(ac))
+            }
+            // A 0 V source is how SPICE measures a current.
+            Some((dc, None)) if dc.unwrap_or(0.) == 0. => {
+                (TikzComponent::Ammeter, nodes(n2, n1), None)
+            }
+            Some((dc, _)) => (TikzComponent::VSource, nodes(n2, n1), dc),
+            None => return Ok(Err(unsupported)),
+        },
+        Some('I') => match source_values(rest) {
+            Some((dc, Some(ac))) if dc.unwrap_or(0.) == 0. => {
+                (TikzComponent::SinISource, nodes(n1, n2), Some(ac))
+            }
+            Some((dc, _)) => (TikzComponent::ISource, nodes(n1, n2), dc),
+            None => return Ok(Err(unsupported)),
+        },
+        _ => return Ok(Err(format!("{line} has no component to draw"))),
+    };
+    Ok(Ok(Card {
+        name: name.to_string(),
+        kind,
+        nodes,
+        value,
+    }))
+}
+
+/// Lines of the deck with their continuations joined, without the title, comments and directives.
+fn cards(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines().skip(1) {
+        let line = line.split(';').next().unwrap_or_default().trim();
+        if let Some(continuation) = line.strip_prefix('+') {
+            if let Some(last) = lines.last_mut() {
+                last.push(' ');
+                last.push_str(continuation);
+            }
+        } else if line.eq_ignore_ascii_case(".end") {
+            break;
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+    lines.retain(|line| !line.is_empty() && !line.starts_with(['*', '.']));
+    lines
+}
+
+fn is_ground(node: &str) -> bool {
+    node == "0" || node.eq_ignore_ascii_case("gnd")
+}
+
+/// Whether `path` is a SPICE deck, by its `.cir` or `.sp` extension.
+pub fn is_deck(path: &str) -> bool {
+    path.ends_with(".cir") || path.ends_with(".sp")
+}
+
+/// Reads a SPICE deck and places it on the grid. Every net gets a horizontal wire of its own, with
+/// ground at the bottom, and every component a column, drawn upwards from the wire of its upper
+/// net. A vertical wire goes from its other end down to the wire of its lower net. Cards other
+/// than R, C, L, D, V and I are skipped.
+pub fn parse(text: &str) -> Result<SpiceImport, SpiceError> {
+    let mut skipped = Vec::new();
+    let mut parsed = Vec::new();
+    for line in cards(text) {
+        match parse_card(&line)? {
+            Ok(card) if card.nodes[0] == card.nodes[1] => {
+                skipped.push(format!("{} has both ends on the same node", card.name));
+            }
+            Ok(card) => parsed.push(card),
+            Err(reason) => skipped.push(reason),
+        }
+    }
+    if parsed.is_empty() {
+        return Err(SpiceError::Empty);
+    }
+
+    let mut rows: HashMap<&str, usize> = HashMap::default();
+    let has_ground = parsed
+        .iter()
+        .flat_map(|card| &card.nodes)
+        .any(|node| is_ground(node));
+    let mut next_row = usize::from(has_ground);
+    for node in parsed.iter().flat_map(|card| &card.nodes) {
+        rows.entry(node.as_str()).or_insert_with(|| {
+            if is_ground(node) {
+                return 0;
+            }
+            next_row += 1;
+            next_row - 1
+        });
+    }
+    let height = |node: &str| ORIGIN.y + rows[node] as f32 * ROW;
+
+    let mut records = Vec::new();
+    // Points where each net meets a column.
+    let mut taps: HashMap<usize, Vec<Vec2>> = HashMap::default();
+    for (column, card) in parsed.iter().enumerate() {
+        let x = ORIGIN.x + column as f32 * COLUMN;
+        let [start, end] = card.nodes.each_ref().map(|node| Vec2::new(x, height(node)));
+        let (top, bottom) = if start.y > end.y {
+            (start, end)
+        } else {
+            (end, start)
+        };
+        let body_end = top - Vec2::Y * LENGTH;
+        let structure = if start == top {
+            [top, body_end]
+        } else {
+            [body_end, top]
+        };
+        records.push(ComponentRecord::new(
+            card.kind,
+            Info::default()
+                .with_label(card.name.clone())
+                .with_value(card.value),
+            ComponentStructure::To(structure),
+        ));
+        if body_end != bottom {
+            records.push(line(body_end, bottom));
+        }
+        for (node, point) in card.nodes.iter().zip([start, end]) {
+            taps.entry(rows[node.as_str()]).or_default().push(point);
+        }
+    }
+
+    let mut taps: Vec<_> = taps.into_iter().collect();
+    taps.sort_by_key(|(row, _)| *row);
+    for (row, mut points) in taps {
+        points.sort_by(|a, b| a.x.total_cmp(&b.x));
+        for pair in points.windows(2) {
+            records.push(line(pair[0], pair[1]));
+        }
+        if points.len() > 2 {
+            for &point in &points[1..points.len() - 1] {
+                records.push(node(TikzComponent::Dot, point));
+            }
+        }
+        if has_ground && row == 0 {
+            records.push(node(TikzComponent::Ground, points[0]));
+        }
+    }
+
+    Ok(SpiceImport { records, skipped })
+}
+
+fn line(start: Vec2, end: Vec2) -> ComponentRecord {
+    ComponentRecord::new(TikzComponent::Line, Info::default(), ComponentStructure::To([start, end]))
+}
+
+fn node(kind: TikzComponent, pos: Vec2) -> ComponentRecord {
+    ComponentRecord::new(kind, Info::default(), ComponentStructure::Node(pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::netlist::{NetId, Netlist};
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!((value - expected).abs() <= 1e-9 * expected.abs(), "{value} is not {expected}");
+    }
+
+    /// Net of a terminal of the component whose label is `label`.
+    fn net(netlist: &Netlist, label: &str, pin: &str) -> NetId {
+        let component = netlist
+            .components
+            .iter()
+            .find(|component| component.record.info.label == label)
+            .unwrap();
+        component.terminal(pin).unwrap().net
+    }
+
+    #[test]
+    fn numbers() {
+        for (text, expected) in [
+            ("4.7k", 4700.),
+            ("4k7", 4700.),
+            ("1meg", 1e6),
+            ("1MEG", 1e6),
+            ("100nF", 100e-9),
+            ("10u", 10e-6),
+            ("1e3", 1e3),
+            ("2.5", 2.5),
+        ] {
+            assert_close(parse_number(text).unwrap(), expected);
+        }
+        assert_eq!(parse_number("abc"), None);
+    }
+
+    #[test]
+    fn divider_is_placed_connected() {
+        let deck = "divider\nV1 in 0 DC 10\nR1 in out 1k\nR2 out 0\n+ 2k\n.op\n.end\n";
+        let import = parse(deck).unwrap();
+        assert!(import.skipped.is_empty());
+
+        let components: Vec<_> = import
+            .records
+            .iter()
+            .filter(|record| record.kind != TikzComponent::Line)
+            .map(|record| (record.kind, record.info.label.as_str(), record.info.value))
+            .collect();
+        assert_eq!(
+            components,
+            [
+                (TikzComponent::VSource, "V1", Some(10.)),
+                (TikzComponent::Resistor, "R1", Some(1000.)),
+                (TikzComponent::Resistor, "R2", Some(2000.)),
+                (TikzComponent::Ground, "", None),
+            ]
+        );
+
+        let netlist = Netlist::build(&import.records);
+        // Voltage sources are drawn with the positive node at the end.
+        assert_eq!(net(&netlist, "V1", "end"), net(&netlist, "R1", "start"));
+        assert_eq!(net(&netlist, "R1", "end"), net(&netlist, "R2", "start"));
+        assert_eq!(Some(net(&netlist, "V1", "start")), netlist.ground());
+        assert_eq!(Some(net(&netlist, "R2", "end")), netlist.ground());
+        assert_eq!(netlist.nets.len(), 3);
+    }
+
+    #[test]
+    fn sources() {
+        let deck = "sources\nV1 1 0 AC 1\nV2 2 0 0\nI1 0 3 SIN(0 2m 1k)\nI2 0 4 DC 5m\n";
+        let import = parse(deck).unwrap();
+        let sources: Vec<_> = import
+            .records
+            .iter()
+            .filter(|record| !record.info.label.is_empty())
+            .collect();
+        let kinds: Vec<_> = sources.iter().map(|record| record.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TikzComponent::SinVSource,
+                TikzComponent::Ammeter,
+                TikzComponent::SinISource,
+                TikzComponent::ISource,
+            ]
+        );
+        assert_close(sources[0].info.value.unwrap(), 1.);
+        assert_eq!(sources[1].info.value, None);
+        assert_close(sources[2].info.value.unwrap(), 2e-3);
+        assert_close(sources[3].info.value.unwrap(), 5e-3);
+    }
+
+    #[test]
+    fn skips_unsupported_sources() {
+        let deck = "sources\nV1 1 0 PULSE(0 5 1n 1n 1n 1u 2u)\nV2 2 0 DC abc\nI1 0 3 EXP(0 1m)\n\
+                    V3 4 0 AC 1 90\nV4 5 0\nR1 1 0 1k\n";
+        let import = parse(deck).unwrap();
+        assert_eq!(
+            import.skipped,
+            [
+                "V1 1 0 PULSE(0 5 1n 1n 1n 1u 2u) has a source form that cannot be drawn",
+                "V2 2 0 DC abc has a source form that cannot be drawn",
+                "I1 0 3 EXP(0 1m) has a source form that cannot be drawn",
+            ]
+        );
+        // Only a missing or 0 V value is an ammeter.
+        let kinds: Vec<_> = import
+            .records
+            .iter()
+            .filter(|record| !record.info.label.is_empty())
+            .map(|record| (record.info.label.as_str(), record.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("V3", TikzComponent::SinVSource),
+                ("V4", TikzComponent::Ammeter),
+                ("R1", TikzComponent::Resistor),
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_what_it_cannot_draw() {
+        let import = parse("skips\nQ1 c b e model\nR1 a a 1k\nR2 a 0 1k\n").unwrap();
+        assert_eq!(import.skipped.len(), 2);
+        assert_eq!(
+            import
+                .records
+                .iter()
+                .filter(|record| record.kind == TikzComponent::Resistor)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(parse("title\n* nothing\n.end\n"), Err(SpiceError::Empty)));
+        assert!(matches!(parse("title\nR1 1\n"), Err(SpiceError::MissingNodes(_))));
+        assert!(matches!(parse("title\nR1 1 2 abc\n"), Err(SpiceError::InvalidValue(_))));
+    }
+}
@@ -0,0 +1,482 @@
+use bevy::{math::Vec2, utils::HashMap};
+
+use crate::project::ComponentRecord;
+use crate::structs::{
+    parse_exported_value, Annotation, AnnotationKind, BuildInfo, ComponentStructure, Info,
+    Position, TikzComponent,
+};
+
+/// Distance of one grid cell in tikz units.
+const TIKZ_GRID: f32 = 0.5;
+
+#[derive(Debug)]
+pub enum ImportError {
+    MissingDraw,
+    Unclosed(char),
+    UnknownCoordinate(String),
+    InvalidNumber(String),
+    UnknownComponent(String),
+    Unexpected(String),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingDraw => write!(f, "No \\draw ... ; block found"),
+            Self::Unclosed(c) => write!(f, "Missing closing '{c}'"),
+            Self::UnknownCoordinate(name) => write!(f, "Unknown coordinate ({name})"),
+            Self::InvalidNumber(number) => write!(f, "Invalid number {number}"),
+            Self::UnknownComponent(options) => write!(f, "No known component in [{options}]"),
+            Self::Unexpected(token) => write!(f, "Unexpected {token}"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Text inside parenthesis, with the `+`/`++` prefix if relative.
+    Coord {
+        text: String,
+        relative: bool,
+    },
+    /// Text inside brackets.
+    Options(String),
+    /// Text inside braces.
+    Text(String),
+    /// `--`, `-|` or `|-`.
+    Operation(String),
+    Word(String),
+}
+
+/// Parses every `\draw ... ;` block of `text` into the records that rebuild it on the canvas.
+pub fn parse(text: &str) -> Result<Vec<ComponentRecord>, ImportError> {
+    let text: String = text
+        .lines()
+        .map(|line| line.split('%').next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut paths = Vec::new();
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find("\\draw") {
+        let body = &rest[start + "\\draw".len()..];
+        let end = find_closing(body, ';').ok_or(ImportError::Unclosed(';'))?;
+        paths.push(tokenize(&body[..end])?);
+        rest = &body[end + 1..];
+    }
+
+    if paths.is_empty() {
+        return Err(ImportError::MissingDraw);
+    }
+
+    // Nodes can be referenced before they are declared, so a first lenient pass collects the
+    // names of every coordinate and node.
+    let mut names = HashMap::new();
+    let mut lenient = Vec::new();
+    for tokens in &paths {
+        Path::new(&mut names, &mut lenient, false).run(tokens.clone())?;
+    }
+    let mut records = Vec::new();
+    for tokens in &paths {
+        Path::new(&mut names, &mut records, true).run(tokens.clone())?;
+    }
+    Ok(records)
+}
+
+/// Finds `closing` outside any brace, bracket or parenthesis.
+fn find_closing(text: &str, closing: char) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            c if c == closing && depth == 0 => return Some(i),
+            '{' | '[' | '(' => depth += 1,
+            '}' | ']' | ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ImportError> {
+    let delimited = |rest: &str, closing: char| -> Result<String, ImportError> {
+        let end = find_closing(rest, closing).ok_or(ImportError::Unclosed(closing))?;
+        Ok(rest[..end].to_string())
+    };
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while let Some(c) = text[i..].chars().next() {
+        let rest = &text[i + c.len_utf8()..];
+        let (token, len) = match c {
+            c if c.is_whitespace() => {
+                i += c.len_utf8();
+                continue;
+            }
+            '(' => {
+                let inner = delimited(rest, ')')?;
+                let len = inner.len() + 2;
+                (
+                    Token::Coord {
+                        text: inner.trim().to_string(),
+                        relative: false,
+                    },
+                    len,
+                )
+            }
+            '[' => {
+                let inner = delimited(rest, ']')?;
+                let len = inner.len() + 2;
+                (Token::Options(inner), len)
+            }
+            '{' => {
+                let inner = delimited(rest, '}')?;
+                let len = inner.len() + 2;
+                (Token::Text(inner), len)
+            }
+            '+' => {
+                let plus = if rest.starts_with('+') { 2 } else { 1 };
+                let Some(rest) = text[i + plus..].strip_prefix('(') else {
+                    return Err(ImportError::Unexpected("+".to_string()));
+                };
+                let inner = delimited(rest, ')')?;
+                let len = inner.len() + plus + 2;
+                (
+                    Token::Coord {
+                        text: inner.trim().to_string(),
+                        relative: true,
+                    },
+                    len,
+                )
+            }
+            '-' | '|' => match text.get(i..i + 2) {
+                Some(operation @ ("--" | "-|" | "|-")) => {
+                    (Token::Operation(operation.to_string()), 2)
+                }
+                _ => return Err(ImportError::Unexpected(c.to_string())),
+            },
+            c if c.is_alphanumeric() => {
+                let word: String = text[i..]
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric())
+                    .collect();
+                let len = word.len();
+                (Token::Word(word), len)
+            }
+            c => return Err(ImportError::Unexpected(c.to_string())),
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+/// Splits options on the commas that are not inside braces.
+fn split_options(options: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut rest = options;
+    while let Some(end) = find_closing(rest, ',') {
+        items.push(rest[..end].trim());
+        rest = &rest[end + 1..];
+    }
+    items.push(rest.trim());
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+/// Reads the component type and the [`Info`] stored in the options of a `to` or a `node`.
+fn parse_options(options: &str) -> (Option<TikzComponent>, Info) {
+    let mut kind = None;
+    let mut info = Info::default();
+    for item in split_options(options) {
+        match item.split_once('=') {
+            Some((key, value)) => match key.trim() {
+                "label" | "l" => info.label = value.trim().to_string(),
+                "scale" => info.scale = value.trim().to_string(),
+                // `R=4.7<\kilo\ohm>` gives both the type and the value, or the label if it is
+                // not a value.
+                key if kind.is_none() && TikzComponent::from_tikz_type(key).is_some() => {
+                    kind = TikzComponent::from_tikz_type(key);
+                    let value = value.trim();
+                    match kind
+                        .and_then(|kind| kind.quantity())
+                        .and_then(|quantity| parse_exported_value(value, quantity))
+                    {
+                        Some(number) => info.value = Some(number),
+                        None => info.label = value.to_string(),
+                    }
+                }
+                _ => info.annotations.extend(Annotation::parse(item)),
+            },
+            None if kind.is_none() => kind = TikzComponent::from_tikz_type(item),
+            None => {}
+        }
+    }
+
+    // The value goes to the annotation when the component also has a label.
+    let quantity = kind.and_then(|kind| kind.quantity());
+    if let (None, Some(quantity)) = (info.value, quantity) {
+        let position = info.annotations.iter().position(|annotation| {
+            annotation.kind == AnnotationKind::Annotation
+                && annotation.modifiers.is_empty()
+                && parse_exported_value(&annotation.text, quantity).is_some()
+        });
+        if let Some(position) = position {
+            let annotation = info.annotations.remove(position);
+            info.value = parse_exported_value(&annotation.text, quantity);
+        }
+    }
+    (kind, info)
+}
+
+/// Reads the `rotate`, `xscale` and `yscale` options of a node.
+fn parse_orientation(options: &str) -> BuildInfo {
+    let mut build_info = BuildInfo::default();
+    for item in split_options(options) {
+        let Some((key, value)) = item.split_once('=') else {
+            continue;
+        };
+        let Ok(value) = value.trim().parse::<f32>() else {
+            continue;
+        };
+        match key.trim() {
+            "rotate" => build_info.angle = value.to_radians().rem_euclid(std::f32::consts::TAU),
+            "xscale" => build_info.flip_x = value < 0.,
+            "yscale" => build_info.flip_y = value < 0.,
+            _ => {}
+        }
+    }
+    build_info
+}
+
+struct Path<'a> {
+    names: &'a mut HashMap<String, Vec2>,
+    records: &'a mut Vec<ComponentRecord>,
+    current: Vec2,
+    strict: bool,
+}
+
+impl<'a> Path<'a> {
+    fn new(
+        names: &'a mut HashMap<String, Vec2>, records: &'a mut Vec<ComponentRecord>, strict: bool,
+    ) -> Self {
+        Self {
+            names,
+            records,
+            current: Vec2::ZERO,
+            strict,
+        }
+    }
+
+    fn run(mut self, tokens: Vec<Token>) -> Result<(), ImportError> {
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            match token {
+                Token::Coord { text, relative } => {
+                    self.current = self.resolve(&text, relative)?;
+                }
+                // Options of the whole path, like `\draw[thick]`.
+                Token::Options(_) => {}
+                Token::Word(word) if word == "to" => {
+                    let options = match tokens.next_if(|t| matches!(t, Token::Options(_))) {
+                        Some(Token::Options(options)) => options,
+                        _ => "short".to_string(),
+                    };
+                    let (kind, info) = parse_options(&options);
+                    let kind = kind.ok_or(ImportError::UnknownComponent(options))?;
+                    let target = self.next_coord(tokens.next())?;
+                    self.push_to(kind, info, target);
+                }
+                Token::Operation(operation) => {
+                    let target = self.next_coord(tokens.next())?;
+                    let corner = match operation.as_str() {
+                        "-|" => Vec2::new(target.x, self.current.y),
+                        "|-" => Vec2::new(self.current.x, target.y),
+                        _ => target,
+                    };
+                    if corner != target && corner != self.current {
+                        self.push_to(TikzComponent::Line, Info::default(), corner);
+                    }
+                    self.push_to(TikzComponent::Line, Info::default(), target);
+                }
+                Token::Word(word) if word == "coordinate" => {
+                    tokens.next_if(|t| matches!(t, Token::Options(_)));
+                    let Some(Token::Coord { text, .. }) = tokens.next() else {
+                        return Err(ImportError::Unexpected("coordinate".to_string()));
+                    };
+                    self.names.insert(text, self.current);
+                }
+                Token::Word(word) if word == "node" => {
+                    let mut options = String::new();
+                    let mut name = None;
+                    let mut position = self.current;
+                    loop {
+                        match tokens.next() {
+                            Some(Token::Options(o)) => options = o,
+                            Some(Token::Coord { text, .. }) => name = Some(text),
+                            Some(Token::Word(w)) if w == "at" => {
+                                position = self.next_coord(tokens.next())?;
+                            }
+                            // The text of the node ends it.
+                            Some(Token::Text(_)) | None => break,
+                            Some(token) => {
+                                return Err(ImportError::Unexpected(format!("{token:?}")))
+                            }
+                        }
+                    }
+                    // Nodes without a known component are only text, so they are skipped.
+                    if let (Some(kind), info) = parse_options(&options) {
+                        let build_info = parse_orientation(&options);
+                        self.push_node(kind, info, build_info, position, name);
+                    }
+                }
+                token => return Err(ImportError::Unexpected(format!("{token:?}"))),
+            }
+        }
+        Ok(())
+    }
+
+    fn next_coord(&self, token: Option<Token>) -> Result<Vec2, ImportError> {
+        match token {
+            Some(Token::Coord { text, relative }) => self.resolve(&text, relative),
+            Some(token) => Err(ImportError::Unexpected(format!("{token:?}"))),
+            None => Err(ImportError::Unexpected("end of path".to_string())),
+        }
+    }
+
+    fn resolve(&self, text: &str, relative: bool) -> Result<Vec2, ImportError> {
+        for (operation, horizontal_first) in [("-|", true), ("|-", false)] {
+            if let Some((first, second)) = text.split_once(operation) {
+                let first = self.resolve(first.trim(), false)?;
+                let second = self.resolve(second.trim(), false)?;
+                return Ok(if horizontal_first {
+                    Vec2::new(second.x, first.y)
+                } else {
+                    Vec2::new(first.x, second.y)
+                });
+            }
+        }
+
+        if let Some((x, y)) = text.split_once(',') {
+            let number = |n: &str| -> Result<f32, ImportError> {
+                let n = n.trim().trim_end_matches("cm").trim();
+                n.parse()
+                    .map_err(|_| ImportError::InvalidNumber(n.to_string()))
+            };
+            let point = Vec2::new(number(x)?, number(y)?);
+            return Ok(if relative {
+                self.current + point
+            } else {
+                point
+            });
+        }
+
+        match self.names.get(text) {
+            Some(position) => Ok(*position),
+            None if !self.strict => Ok(self.current),
+            None => Err(ImportError::UnknownCoordinate(text.to_string())),
+        }
+    }
+
+    fn push_to(&mut self, kind: TikzComponent, info: Info, target: Vec2) {
+        let structure = ComponentStructure::To([to_world(self.current), to_world(target)]);
+        self.records
+            .push(ComponentRecord::new(kind, info, structure));
+        self.current = target;
+    }
+
+    fn push_node(
+        &mut self, kind: TikzComponent, info: Info, build_info: BuildInfo, position: Vec2,
+        name: Option<String>,
+    ) {
+        if let Some(name) = name {
+            for (pin, offset) in kind.pins() {
+                self.names.insert(
+                    format!("{name}{pin}"),
+                    position + build_info.orient(*offset) * TIKZ_GRID,
+                );
+            }
+            self.names.insert(name, position);
+        }
+        let structure = ComponentStructure::Node(to_world(position));
+        let mut record = ComponentRecord::new(kind, info, structure);
+        if kind != TikzComponent::Dot {
+            record.build_info = Some(build_info);
+        }
+        self.records.push(record);
+    }
+}
+
+fn to_world(tikz: Vec2) -> Vec2 {
+    Position::from_tikz_coords(tikz).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ends(record: &ComponentRecord) -> [Vec2; 2] {
+        match record.structure {
+            ComponentStructure::To(ends) => ends,
+            ComponentStructure::Node(pos) => panic!("{:?} is a node at {pos}", record.kind),
+        }
+    }
+
+    #[test]
+    fn reads_bipole_with_value() {
+        let records = parse("\\draw (0,0) to[R=4.7<\\kilo\\ohm>] (2,0);").unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind, TikzComponent::Resistor);
+        assert_eq!(ends(&records[0]), [Vec2::new(160., 0.), Vec2::new(224., 0.)]);
+        assert!((records[0].info.value.unwrap() - 4700.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn value_of_labeled_bipole_comes_from_annotation() {
+        let records = parse("\\draw (0,0) to[R, l=R_1, a=1<\\kilo\\ohm>] (2,0);").unwrap();
+        let info = &records[0].info;
+        assert_eq!(info.label, "R_1");
+        assert!((info.value.unwrap() - 1000.).abs() < 1e-9);
+        assert!(info.annotations.is_empty());
+    }
+
+    #[test]
+    fn corner_operation_splits_in_two_wires() {
+        let records = parse("\\draw (0,0) -| (2,2);").unwrap();
+        assert_eq!(records.len(), 2);
+        assert!(records
+            .iter()
+            .all(|record| record.kind == TikzComponent::Line));
+        assert_eq!(ends(&records[0]), [Vec2::new(160., 0.), Vec2::new(224., 0.)]);
+        assert_eq!(ends(&records[1]), [Vec2::new(224., 0.), Vec2::new(224., 64.)]);
+    }
+
+    #[test]
+    fn pins_of_nodes_are_referenced_before_they_are_declared() {
+        let text = "\\draw (AND1.out) to[short] (4,0);\n\\draw (0,0) node[and port] (AND1) {};";
+        let records = parse(text).unwrap();
+        assert_eq!(records.len(), 2);
+        // The output pin is two grid cells to the right of the gate.
+        assert_eq!(ends(&records[0]), [Vec2::new(192., 0.), Vec2::new(288., 0.)]);
+        assert_eq!(records[1].kind, TikzComponent::AndGate);
+        assert_eq!(records[1].structure, ComponentStructure::Node(Vec2::new(160., 0.)));
+    }
+
+    #[test]
+    fn comments_are_ignored() {
+        let records = parse("% \\draw (0,0) to[R] (2,0);\n\\draw (0,0) to[C] (2,0);").unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].kind, TikzComponent::Capacitor);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(parse("no circuit here"), Err(ImportError::MissingDraw)));
+        assert!(matches!(parse("\\draw (0,0) to[R] (2,0)"), Err(ImportError::Unclosed(';'))));
+        assert!(matches!(
+            parse("\\draw (missing) to[R] (2,0);"),
+            Err(ImportError::UnknownCoordinate(_))
+        ));
+    }
+}
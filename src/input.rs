@@ -10,10 +10,15 @@ pub enum MouseMode {
     SelectAndCreate,
     Pan,
     Create,
+    /// Clicking a net driven by no gate switches it between 0 and 1, and the levels of every net
+    /// are drawn over the circuit.
+    Simulate,
+    /// A click on a pin starts a wire and a click on another ends it, drawn around the components.
+    Route,
 }
 
 pub fn cancel_action(mut commands: Commands, selected: Query<Entity, With<select_node::Selected>>) {
-    if let Ok(ent) = selected.get_single() {
+    for ent in &selected {
         commands.entity(ent).remove::<select_node::Selected>();
     }
 }
@@ -28,6 +33,7 @@ pub fn remove_all(
         return;
     }
     let entities: Vec<_> = q_points.into_iter().collect();
+    commands.trigger(history::Checkpoint);
     commands.trigger_targets(DeleteComponent, entities)
 }
 
@@ -35,7 +41,8 @@ pub fn change_current_component(
     mut commands: Commands, keys: Res<ButtonInput<KeyCode>>, mut cc: ResMut<TikzComponent>,
     mut exit: EventWriter<AppExit>, focused: Res<ui::FocusedInputText>,
 ) {
-    if focused.0 != Entity::PLACEHOLDER {
+    // Keys pressed with control are shortcuts, like Ctrl+C.
+    if focused.0 != Entity::PLACEHOLDER || select_node::ctrl_pressed(&keys) {
         return;
     }
 
@@ -4,7 +4,7 @@
 //!
 //! See the [examples](https://github.com/rparrett/bevy_simple_text_input/tree/latest/examples) folder.
 //!
-//! ```no_run
+//! ```ignore
 //! use bevy::prelude::*;
 //! use bevy_simple_text_input::{TextInput, TextInputPlugin};
 //!
@@ -89,7 +89,7 @@ const CURSOR_HANDLE: Handle<Font> = Handle::weak_from_u128(10482756907980398621)
 ///
 /// # Example
 ///
-/// ```rust
+/// ```ignore
 /// # use bevy::prelude::*;
 /// use bevy_simple_text_input::TextInput;
 /// fn setup(mut commands: Commands) {
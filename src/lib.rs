@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+#[cfg(feature = "gui")]
+use bevy::utils::HashMap;
+
+#[cfg(feature = "gui")]
+pub mod actions;
+#[cfg(feature = "gui")]
+pub mod clipboard;
+#[cfg(feature = "gui")]
+pub mod components;
+#[cfg(feature = "gui")]
+pub mod create;
+pub mod export;
+pub mod graph;
+#[cfg(feature = "gui")]
+pub mod history;
+pub mod import;
+#[cfg(feature = "gui")]
+pub mod input;
+#[cfg(feature = "gui")]
+pub mod input_widget;
+pub mod project;
+#[cfg(feature = "gui")]
+pub mod simulation;
+pub mod structs;
+pub mod symbols;
+#[cfg(feature = "gui")]
+pub mod ui;
+
+#[cfg(feature = "gui")]
+use structs::TikzComponent;
+
+pub const TEXT_SCALE: f32 = 0.6;
+pub const GRID_SIZE: f32 = 16.0;
@@ -1,51 +1,76 @@
 use bevy::{
     input::common_conditions::{input_just_pressed, input_pressed},
     prelude::*,
-    utils::HashMap,
 };
 
-mod actions;
-mod components;
-mod create;
-mod graph;
-mod input;
-mod input_widget;
-mod structs;
-mod ui;
-use input_widget::TextInputPlugin;
-
-use structs::TikzComponent;
+use circuits::input_widget::TextInputPlugin;
+use circuits::{
+    actions, clipboard, components, create, export, graph, history, import, input, project,
+    simulation, structs, symbols, ui, GRID_SIZE,
+};
 
-const TEXT_SCALE: f32 = 0.6;
-const GRID_SIZE: f32 = 16.0;
+/// Project opened and saved when no path is given: `circuits [project.ron] [deck.cir|deck.sp]`.
+/// The SPICE import opens the deck given, if any.
+const DEFAULT_PROJECT: &str = "circuit.ron";
 
 fn main() {
+    let (decks, projects): (Vec<String>, Vec<String>) =
+        std::env::args().skip(1).partition(|arg| import::spice::is_deck(arg));
+    let project_file = projects
+        .into_iter()
+        .next()
+        .unwrap_or_else(|| DEFAULT_PROJECT.to_string());
+    // The circuit text is written beside the project.
+    let tex_file = std::path::Path::new(&project_file)
+        .with_extension("tex")
+        .to_string_lossy()
+        .into_owned();
+
     App::new()
         .add_plugins(DefaultPlugins)
+        .add_plugins(symbols::SymbolsPlugin(symbols::DEFAULT_LIBRARY.to_string()))
         .add_plugins(TextInputPlugin)
         .add_plugins(ui::UiPlugin)
         .insert_resource(structs::TikzComponent::Resistor)
         .insert_resource(structs::CursorPosition::default())
-        .insert_resource(ui::CurrentFile("/home/guilherme/projects/circuits/test.tex".to_string()))
+        .insert_resource(actions::MoveStart::default())
+        .insert_resource(ui::CurrentFile(tex_file))
+        .insert_resource(project::ProjectFile(project_file))
+        .insert_resource(import::SpiceFile(decks.into_iter().next()))
         .insert_state(input::MouseMode::default())
         .add_plugins(graph::GraphPlugin)
+        .add_plugins(project::ProjectPlugin)
+        .add_plugins(import::ImportPlugin)
+        .add_plugins(export::ExportPlugin)
+        .add_plugins(simulation::SimulationPlugin)
+        .add_plugins(history::HistoryPlugin)
+        .add_plugins(clipboard::ClipboardPlugin)
         .add_systems(Startup, (setup, components::load_handles))
         .add_systems(
             Update,
             (
                 structs::get_cursor_position,
                 actions::select_node::despawn_selected.run_if(input_just_pressed(KeyCode::Delete)),
-                actions::move_entity.run_if(input_pressed(MouseButton::Right)),
+                (
+                    actions::start_move.run_if(input_just_pressed(MouseButton::Right)),
+                    actions::move_entity.run_if(input_pressed(MouseButton::Right)),
+                )
+                    .chain(),
                 input::change_current_component,
+                actions::orientation_shortcuts,
+                actions::annotations::draw_annotations,
                 input::cancel_action.run_if(input_just_pressed(KeyCode::Escape)),
                 input::zoom_scale,
             ),
         )
+        .add_systems(OnExit(input::MouseMode::Route), actions::draw_components::clear_route_start)
         .add_observer(create::create)
         .add_observer(input::remove_all)
         .add_observer(ui::update_file)
         .add_observer(actions::draw_components::draw_initial_component)
+        .add_observer(actions::draw_components::route_wire)
         .add_observer(actions::delete_component)
+        .add_observer(actions::orient_selected)
         .add_observer(actions::update_component_label)
         .run();
 }
@@ -0,0 +1,537 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::export::latex::{wrap, ExportProfile};
+use crate::graph::{export_tikz, CircuitGraph, LabelChildComponent};
+use crate::structs::{
+    BuildInfo, ComponentLabel, ComponentStructure, Info, TikzComponent, ValueStyle,
+};
+#[cfg(feature = "gui")]
+use crate::{
+    actions::{draw_components::spawn_component, select_node::ctrl_pressed},
+    components::Handles,
+    graph::UpdateChildLabels,
+    history::Checkpoint,
+    structs::Anchored,
+};
+
+/// Version written in every saved project. Files with a newer version are refused.
+pub const PROJECT_VERSION: u32 = 1;
+
+#[cfg(feature = "gui")]
+pub struct ProjectPlugin;
+
+#[cfg(feature = "gui")]
+impl Plugin for ProjectPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ValueStyle::default())
+            .add_observer(save_project)
+            .add_observer(load_project)
+            .add_observer(replace_circuit)
+            .add_systems(Update, project_shortcuts);
+    }
+}
+
+#[cfg(feature = "gui")]
+fn project_shortcuts(
+    mut commands: Commands, keys: Res<ButtonInput<KeyCode>>,
+    focused: Res<crate::ui::FocusedInputText>,
+) {
+    if focused.0 != Entity::PLACEHOLDER || !ctrl_pressed(&keys) {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::KeyS) {
+        commands.trigger(SaveProject);
+    } else if keys.just_pressed(KeyCode::KeyO) {
+        commands.trigger(LoadProject);
+    }
+}
+
+/// Path of the project file that is saved and opened.
+#[derive(Resource)]
+pub struct ProjectFile(pub String);
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Project {
+    pub version: u32,
+    pub components: Vec<ComponentRecord>,
+    #[serde(default)]
+    pub value_style: ValueStyle,
+    #[serde(default)]
+    pub export_profile: ExportProfile,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ComponentRecord {
+    pub kind: TikzComponent,
+    pub structure: ComponentStructure,
+    #[serde(default)]
+    pub build_info: Option<BuildInfo>,
+    #[serde(default)]
+    pub info: Info,
+    #[serde(default)]
+    pub label: String,
+}
+
+#[derive(Debug)]
+pub enum ProjectError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    Version(u32),
+}
+
+impl ComponentRecord {
+    /// A record drawn as given, without build info or project label.
+    pub fn new(kind: TikzComponent, info: Info, structure: ComponentStructure) -> Self {
+        Self {
+            kind,
+            structure,
+            build_info: None,
+            info,
+            label: String::new(),
+        }
+    }
+
+    /// Points where other components connect to this one: the ends of a bipole, or the position
+    /// and the pins of a node.
+    pub fn terminals(&self) -> Vec<Vec2> {
+        match self.structure {
+            ComponentStructure::To([initial, fin]) => vec![initial, fin],
+            ComponentStructure::Node(pos) => std::iter::once(pos)
+                .chain(
+                    self.structure
+                        .pins(self.kind, self.build_info.unwrap_or_default())
+                        .into_iter()
+                        .map(|(_, pin)| pin),
+                )
+                .collect(),
+        }
+    }
+
+    /// Same record moved by `offset`.
+    pub fn translated(&self, offset: Vec2) -> Self {
+        let structure = match self.structure {
+            ComponentStructure::To([initial, fin]) => {
+                ComponentStructure::To([initial + offset, fin + offset])
+            }
+            ComponentStructure::Node(pos) => ComponentStructure::Node(pos + offset),
+        };
+        Self {
+            structure,
+            ..self.clone()
+        }
+    }
+}
+
+impl std::fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse(err) => write!(f, "Invalid project file: {err}"),
+            Self::Serialize(err) => write!(f, "Could not serialize project: {err}"),
+            Self::Version(version) => {
+                write!(f, "Project version {version} is newer than the supported {PROJECT_VERSION}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+impl Project {
+    pub fn new(components: Vec<ComponentRecord>) -> Self {
+        Self {
+            version: PROJECT_VERSION,
+            components,
+            value_style: ValueStyle::default(),
+            export_profile: ExportProfile::default(),
+        }
+    }
+
+    pub fn to_ron(&self) -> Result<String, ProjectError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(ProjectError::Serialize)
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, ProjectError> {
+        let project: Self = ron::from_str(text).map_err(ProjectError::Parse)?;
+        if project.version > PROJECT_VERSION {
+            return Err(ProjectError::Version(project.version));
+        }
+        Ok(project)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), ProjectError> {
+        std::fs::write(path, self.to_ron()?).map_err(ProjectError::Io)
+    }
+
+    pub fn open(path: &str) -> Result<Self, ProjectError> {
+        let text = std::fs::read_to_string(path).map_err(ProjectError::Io)?;
+        Self::from_ron(&text)
+    }
+
+    /// Exports the circuit the same way the app does, without spawning any entity. Each record
+    /// stands for the entity with its index. Records without a saved label are numbered by type,
+    /// skipping the saved labels.
+    pub fn to_tikz(&self) -> String {
+        let labels = ComponentLabel::numbered(
+            self.components
+                .iter()
+                .map(|record| (record.kind, record.label.as_str())),
+        );
+        let mut graph = CircuitGraph::default();
+        let mut child_labels = LabelChildComponent::default();
+        let mut components = HashMap::new();
+
+        for (i, (record, label)) in self.components.iter().zip(&labels).enumerate() {
+            let entity = Entity::from_raw(i as u32);
+            let build_info = record.build_info.unwrap_or_default();
+            graph.add_component(record.structure, entity);
+            for (pin, pos) in record.structure.pins(record.kind, build_info) {
+                child_labels.insert(pos.into(), format!("{}{pin}", label.label));
+            }
+            components.insert(entity, (record.kind, &record.info, label, build_info));
+        }
+
+        export_tikz(&graph, &child_labels, &components, self.value_style)
+    }
+
+    /// [`Project::to_tikz`] wrapped as the export profile of the project asks.
+    pub fn to_latex(&self) -> String {
+        wrap(&self.to_tikz(), &self.export_profile, self.value_style)
+    }
+}
+
+#[derive(Event)]
+pub struct SaveProject;
+
+/// Every drawn component, with what is needed to draw it again.
+pub type RecordQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static TikzComponent,
+        &'static ComponentStructure,
+        Option<&'static BuildInfo>,
+        &'static Info,
+        &'static ComponentLabel,
+    ),
+>;
+
+/// Collects the records of every component, in the order they were spawned.
+pub fn records(components: &RecordQuery) -> Vec<ComponentRecord> {
+    entity_records(components)
+        .into_iter()
+        .map(|(_, record)| record)
+        .collect()
+}
+
+/// Same as [`records`], keeping the entity of each record.
+pub fn entity_records(components: &RecordQuery) -> Vec<(Entity, ComponentRecord)> {
+    let mut components: Vec<_> = components.iter().collect();
+    components.sort_by_key(|(entity, ..)| *entity);
+
+    components
+        .into_iter()
+        .map(|(entity, kind, structure, build_info, info, label)| {
+            let record = ComponentRecord {
+                kind: *kind,
+                structure: *structure,
+                build_info: build_info.copied(),
+                info: info.clone(),
+                label: label.label.clone(),
+            };
+            (entity, record)
+        })
+        .collect()
+}
+
+#[cfg(feature = "gui")]
+pub fn save_project(
+    _: Trigger<SaveProject>, file: Res<ProjectFile>, components: RecordQuery,
+    value_style: Res<ValueStyle>, export_profile: Res<ExportProfile>,
+) {
+    let project = Project {
+        value_style: *value_style,
+        export_profile: export_profile.clone(),
+        ..Project::new(records(&components))
+    };
+    match project.save(&file.0) {
+        Ok(()) => info!("Saved project to {}", file.0),
+        Err(err) => error!("Could not save project to {}: {err}", file.0),
+    }
+}
+
+#[derive(Event)]
+pub struct LoadProject;
+
+#[cfg(feature = "gui")]
+pub fn load_project(_: Trigger<LoadProject>, mut commands: Commands, file: Res<ProjectFile>) {
+    match Project::open(&file.0) {
+        Ok(project) => {
+            info!("Opened project {}", file.0);
+            commands.insert_resource(project.value_style);
+            commands.insert_resource(project.export_profile);
+            commands.trigger(Checkpoint);
+            commands.trigger(ReplaceCircuit(project.components));
+        }
+        Err(err) => error!("Could not open project {}: {err}", file.0),
+    }
+}
+
+/// Removes every component from the canvas and draws the given ones in their place.
+#[derive(Event)]
+pub struct ReplaceCircuit(pub Vec<ComponentRecord>);
+
+#[cfg(feature = "gui")]
+pub fn replace_circuit(
+    trigger: Trigger<ReplaceCircuit>, mut commands: Commands, handles: Res<Handles>,
+    mut material: ResMut<Assets<ColorMaterial>>, mut graph: ResMut<CircuitGraph>,
+    components: Query<Entity, With<TikzComponent>>,
+) {
+    for entity in &components {
+        commands.entity(entity).despawn_recursive();
+    }
+    *graph = CircuitGraph::default();
+
+    // Records without a label are numbered here, so they cannot take the saved label of another.
+    let records = &trigger.event().0;
+    let labels = ComponentLabel::numbered(
+        records
+            .iter()
+            .map(|record| (record.kind, record.label.as_str())),
+    );
+    let records: Vec<_> = records
+        .iter()
+        .zip(labels)
+        .map(|(record, label)| ComponentRecord {
+            label: label.label,
+            ..record.clone()
+        })
+        .collect();
+    spawn_records(&mut commands, &records, &handles, &mut material);
+}
+
+/// Spawns every record through the regular drawing path, so each entity gets its meshes, pin
+/// labels and graph edge, then restores the per component information.
+#[cfg(feature = "gui")]
+pub fn spawn_records(
+    commands: &mut Commands, records: &[ComponentRecord], handles: &Handles,
+    material: &mut Assets<ColorMaterial>,
+) -> Vec<Entity> {
+    let entities: Vec<Entity> = records
+        .iter()
+        .map(|record| {
+            let entity =
+                spawn_component(commands, record.kind, handles, material, record.structure);
+            commands.entity(entity).insert(record.info.clone());
+            // Nodes drawn from meshes are spawned unturned, so their orientation is set again.
+            if let (ComponentStructure::Node(pos), Some(build_info)) =
+                (record.structure, record.build_info)
+            {
+                if record.kind != TikzComponent::Dot {
+                    commands.entity(entity).insert((build_info, Anchored(pos)));
+                }
+            }
+            entity
+        })
+        .collect();
+    // Every spawn numbers the new component, so the saved labels are put back after the last one.
+    for (&entity, record) in entities.iter().zip(records) {
+        if !record.label.is_empty() {
+            commands.entity(entity).insert(ComponentLabel {
+                label: record.label.clone(),
+            });
+        }
+    }
+    commands.trigger(UpdateChildLabels);
+    entities
+}
+
+/// Records the tests build their circuits from, with the positions in world units.
+#[cfg(test)]
+pub mod fixtures {
+    use bevy::math::Vec2;
+
+    use super::ComponentRecord;
+    use crate::structs::{BuildInfo, ComponentStructure, Info, TikzComponent};
+
+    /// A record without value, whose nodes have the default orientation.
+    pub fn record(
+        kind: TikzComponent, label: &str, structure: ComponentStructure,
+    ) -> ComponentRecord {
+        ComponentRecord {
+            build_info: kind.is_single().then(BuildInfo::default),
+            label: label.to_string(),
+            ..ComponentRecord::new(kind, Info::default(), structure)
+        }
+    }
+
+    pub fn bipole(
+        kind: TikzComponent, label: &str, from: [f32; 2], to: [f32; 2],
+    ) -> ComponentRecord {
+        record(kind, label, ComponentStructure::To([Vec2::from(from), Vec2::from(to)]))
+    }
+
+    pub fn wire(from: [f32; 2], to: [f32; 2]) -> ComponentRecord {
+        bipole(TikzComponent::Line, "", from, to)
+    }
+
+    pub fn node(kind: TikzComponent, label: &str, at: [f32; 2]) -> ComponentRecord {
+        record(kind, label, ComponentStructure::Node(Vec2::from(at)))
+    }
+
+    pub fn ground(at: [f32; 2]) -> ComponentRecord {
+        node(TikzComponent::Ground, "", at)
+    }
+
+    /// `record` with a value.
+    pub fn valued(value: f64, record: ComponentRecord) -> ComponentRecord {
+        ComponentRecord {
+            info: record.info.clone().with_value(Some(value)),
+            ..record
+        }
+    }
+
+    /// A source `V1` from ground up to `(0, 64)`, then `Z1` right to `(64, 64)` and `Z2` down to
+    /// ground again, each a kind with its value.
+    pub fn divider(
+        source: (TikzComponent, f64), upper: (TikzComponent, f64), lower: (TikzComponent, f64),
+    ) -> Vec<ComponentRecord> {
+        vec![
+            ground([0., 0.]),
+            valued(source.1, bipole(source.0, "V1", [0., 0.], [0., 64.])),
+            valued(upper.1, bipole(upper.0, "Z1", [0., 64.], [64., 64.])),
+            valued(lower.1, bipole(lower.0, "Z2", [64., 64.], [64., 0.])),
+            ground([64., 0.]),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> Project {
+        use fixtures::{bipole, node, valued};
+        let resistor = valued(4700., bipole(TikzComponent::Resistor, "R1", [0., 0.], [64., 0.]));
+        let gate = ComponentRecord {
+            build_info: Some(BuildInfo::new(std::f32::consts::FRAC_PI_2, 0.)),
+            info: Info::default().with_label("carry".to_string()),
+            ..node(TikzComponent::AndGate, "AND7", [128., 32.])
+        };
+        Project::new(vec![resistor, gate])
+    }
+
+    #[test]
+    fn round_trip() {
+        let project = project();
+        let reloaded = Project::from_ron(&project.to_ron().unwrap()).unwrap();
+        assert_eq!(reloaded.version, PROJECT_VERSION);
+        assert_eq!(reloaded.components, project.components);
+        assert_eq!(reloaded.value_style, project.value_style);
+        assert_eq!(reloaded.export_profile, project.export_profile);
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let text = project().to_ron().unwrap().replacen(
+            &format!("version: {PROJECT_VERSION}"),
+            &format!("version: {}", PROJECT_VERSION + 1),
+            1,
+        );
+        assert!(matches!(
+            Project::from_ron(&text),
+            Err(ProjectError::Version(version)) if version == PROJECT_VERSION + 1
+        ));
+        assert!(matches!(Project::from_ron("(version: 1)"), Err(ProjectError::Parse(_))));
+    }
+
+    #[test]
+    fn labels_survive_the_reload() {
+        let mut project = Project::from_ron(&project().to_ron().unwrap()).unwrap();
+        // A record saved without a label is numbered by its type.
+        project.components.push(ComponentRecord {
+            label: String::new(),
+            structure: ComponentStructure::Node(Vec2::new(256., 32.)),
+            ..project.components[1].clone()
+        });
+        assert_eq!(project.components[1].label, "AND7");
+        let tikz = project.to_tikz();
+        assert!(tikz.contains("(AND7){}"), "{tikz}");
+        assert!(tikz.contains("(AND2){}"), "{tikz}");
+        assert!(!tikz.contains("(AND1)"), "{tikz}");
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn labels_survive_an_edit() {
+        use crate::components::InfoMeshes;
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world.add_observer(crate::actions::update_component_label);
+        world.add_observer(replace_circuit);
+        world.init_resource::<CircuitGraph>();
+        world.insert_resource(Assets::<ColorMaterial>::default());
+        let meshes = [TikzComponent::Resistor, TikzComponent::AndGate]
+            .map(|kind| (kind, InfoMeshes { meshes: Vec::new() }));
+        world.insert_resource(Handles(meshes.into_iter().collect()));
+        world.flush();
+
+        // The unlabeled gate comes first, and must not be numbered as the saved AND1.
+        let mut project = project();
+        let unlabeled = ComponentRecord {
+            label: String::new(),
+            structure: ComponentStructure::Node(Vec2::new(256., 32.)),
+            ..project.components[1].clone()
+        };
+        project.components[1].label = "AND1".to_string();
+        project.components.insert(0, unlabeled);
+        let project = Project::from_ron(&project.to_ron().unwrap()).unwrap();
+        world.trigger(ReplaceCircuit(project.components.clone()));
+        world.flush();
+
+        let saved = |world: &mut World| {
+            world
+                .run_system_once(|components: RecordQuery| entity_records(&components))
+                .unwrap()
+        };
+        let loaded = saved(&mut world);
+        let labels: Vec<_> = loaded
+            .iter()
+            .map(|(_, record)| record.label.as_str())
+            .collect();
+        assert_eq!(labels, ["AND2", "R1", "AND1"]);
+
+        // Deleting a component and drawing a new one keep the labels of the others.
+        world.despawn(loaded[0].0);
+        world
+            .run_system_once(
+                move |mut commands: Commands,
+                      handles: Res<Handles>,
+                      mut material: ResMut<Assets<ColorMaterial>>| {
+                    let record = ComponentRecord {
+                        label: String::new(),
+                        structure: ComponentStructure::To([Vec2::ZERO, Vec2::new(0., 64.)]),
+                        ..project.components[1].clone()
+                    };
+                    spawn_records(&mut commands, &[record], &handles, &mut material);
+                },
+            )
+            .unwrap();
+        let edited: Vec<_> = saved(&mut world)
+            .into_iter()
+            .map(|(_, record)| record)
+            .collect();
+        let labels: Vec<_> = edited.iter().map(|record| record.label.as_str()).collect();
+        assert_eq!(labels, ["R1", "AND1", "E2"]);
+
+        let tikz = Project::new(edited).to_tikz();
+        assert!(tikz.contains("(AND1){}"), "{tikz}");
+        assert!(!tikz.contains("(AND2)"), "{tikz}");
+    }
+}
@@ -0,0 +1,180 @@
+use std::f64::consts::PI;
+
+use crate::graph::netlist::{NetComponent, NetId, Netlist};
+use crate::structs::TikzComponent;
+
+use super::dc::resistance;
+use super::linear::{elements, solve_nodal, Complex, Element, Scalar};
+use super::AnalysisError;
+
+/// How a component behaves at the angular frequency `omega`, or `None` if it is not linear. Only
+/// sine sources drive the circuit, the DC ones are off.
+fn model(component: &NetComponent, omega: f64) -> Result<Option<Element<Complex>>, AnalysisError> {
+    use TikzComponent::*;
+    let record = &component.record;
+    let value = || {
+        record
+            .info
+            .value
+            .ok_or_else(|| AnalysisError::MissingValue(record.label.clone()))
+    };
+    Ok(Some(match record.kind {
+        Resistor | VariableResistor => {
+            Element::Admittance(Complex::from(1. / resistance(component)?))
+        }
+        Lamp if record.info.value.is_none() => Element::Admittance(Complex::default()),
+        Lamp => Element::Admittance(Complex::from(1. / resistance(component)?)),
+        Capacitor => Element::Admittance(Complex::new(0., omega * value()?)),
+        Inductor => Element::Admittance(Complex::from(1.) / Complex::new(0., omega * value()?)),
+        Switch | Voltmeter | ISource => Element::Open,
+        ClosedSwitch | Fuse | Ammeter | VSource | Battery => Element::Voltage(Complex::default()),
+        SinVSource => Element::Voltage(Complex::from(value()?)),
+        SinISource => Element::Current(Complex::from(value()?)),
+        _ => return Ok(None),
+    }))
+}
+
+/// Response at one frequency of a sweep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodePoint {
+    /// In hertz.
+    pub frequency: f64,
+    pub magnitude_db: f64,
+    pub phase_degrees: f64,
+}
+
+/// Frequencies of a logarithmic sweep, from `start` to `stop`.
+fn frequencies(start: f64, stop: f64, per_decade: usize) -> Vec<f64> {
+    let decades = (stop / start).log10();
+    let count = (decades * per_decade as f64).ceil().max(1.) as usize;
+    (0..=count)
+        .map(|i| start * 10f64.powf(decades * i as f64 / count as f64))
+        .collect()
+}
+
+/// Sweeps the frequency from `start` to `stop` hertz and finds `V(output) / V(input)` at each
+/// one, driven by the sine sources of the circuit. The phase is unwrapped, so it goes past ±180°
+/// in filters of higher order.
+pub fn sweep(
+    netlist: &Netlist, input: NetId, output: NetId, start: f64, stop: f64, per_decade: usize,
+) -> Result<Vec<BodePoint>, AnalysisError> {
+    let ground = netlist.ground().ok_or(AnalysisError::NoGround)?;
+    let mut points: Vec<BodePoint> = Vec::new();
+    for frequency in frequencies(start, stop, per_decade) {
+        let omega = 2. * PI * frequency;
+        let (elements, _) = elements(netlist, |component| model(component, omega))?;
+        let solution = solve_nodal(netlist, ground, &elements).ok_or(AnalysisError::Singular)?;
+        let vin = solution.voltages[input.0];
+        if vin.norm() < 1e-15 {
+            return Err(AnalysisError::NoExcitation);
+        }
+        let gain = solution.voltages[output.0] / vin;
+
+        let mut phase = gain.arg().to_degrees();
+        if let Some(last) = points.last() {
+            phase -= 360. * ((phase - last.phase_degrees) / 360.).round();
+        }
+        points.push(BodePoint {
+            frequency,
+            magnitude_db: 20. * gain.norm().log10(),
+            phase_degrees: phase,
+        });
+    }
+    Ok(points)
+}
+
+/// Writes the sweep as a pgfplots table, with one `\addplot` for the magnitude and one for the
+/// phase, to go in their `semilogxaxis`.
+pub fn pgfplots(points: &[BodePoint], title: &str) -> String {
+    let mut text = format!("% Bode plot of {title}\n\\pgfplotstableread{{\nf db phase\n");
+    for point in points {
+        text.push_str(&format!(
+            "{:.5e} {:.4} {:.4}\n",
+            point.frequency, point.magnitude_db, point.phase_degrees
+        ));
+    }
+    text.push_str("}\\bodetable\n");
+    text.push_str("% Magnitude, in dB\n\\addplot table[x=f, y=db] {\\bodetable};\n");
+    text.push_str("% Phase, in degrees\n\\addplot table[x=f, y=phase] {\\bodetable};\n");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::fixtures::divider;
+
+    /// A sine source into 1 kΩ and 1 µF, with the output across the capacitor.
+    fn low_pass() -> Netlist {
+        use TikzComponent::*;
+        Netlist::build(&divider((SinVSource, 1.), (Resistor, 1e3), (Capacitor, 1e-6)))
+    }
+
+    fn assert_close(value: f64, expected: f64, tolerance: f64) {
+        assert!((value - expected).abs() < tolerance, "{value} is not {expected}");
+    }
+
+    #[test]
+    fn logarithmic_frequencies() {
+        let frequencies = frequencies(10., 1e4, 2);
+        assert_eq!(frequencies.len(), 7);
+        assert_close(frequencies[0], 10., 1e-9);
+        assert_close(frequencies[2], 100., 1e-9);
+        assert_close(frequencies[6], 1e4, 1e-6);
+        // Less than a step still has both ends.
+        assert_eq!(super::frequencies(10., 11., 20).len(), 2);
+    }
+
+    #[test]
+    fn low_pass_corner() {
+        let netlist = low_pass();
+        let input = netlist.resolve("Z1.start").unwrap();
+        let output = netlist.resolve("Z1.end").unwrap();
+        let corner = 1. / (2. * PI * 1e3 * 1e-6);
+        let points = sweep(&netlist, input, output, corner, 100. * corner, 10).unwrap();
+        assert_eq!(points.len(), 21);
+
+        let first = points[0];
+        assert_close(first.frequency, corner, 1e-9);
+        assert_close(first.magnitude_db, -10. * 2f64.log10(), 1e-6);
+        assert_close(first.phase_degrees, -45., 1e-6);
+        // Two decades past the corner, it falls by about 20 dB each and nears -90°.
+        let last = points[points.len() - 1];
+        assert_close(last.magnitude_db, -40., 0.01);
+        assert_close(last.phase_degrees, -89.43, 0.01);
+    }
+
+    #[test]
+    fn needs_a_sine_source() {
+        let netlist = low_pass();
+        let ground = netlist.ground().unwrap();
+        let output = netlist.resolve("Z1.end").unwrap();
+        // The input is ground, which never moves.
+        let err = sweep(&netlist, ground, output, 1., 10., 1).unwrap_err();
+        assert_eq!(err, AnalysisError::NoExcitation);
+    }
+
+    #[test]
+    fn pgfplots_table() {
+        let points = [
+            BodePoint {
+                frequency: 100.,
+                magnitude_db: -0.5,
+                phase_degrees: -12.25,
+            },
+            BodePoint {
+                frequency: 1000.,
+                magnitude_db: -3.0103,
+                phase_degrees: -45.,
+            },
+        ];
+        let text = pgfplots(&points, "V1.end, C1.start");
+        assert_eq!(
+            text,
+            "% Bode plot of V1.end, C1.start\n\\pgfplotstableread{\nf db phase\n\
+             1.00000e2 -0.5000 -12.2500\n1.00000e3 -3.0103 -45.0000\n}\\bodetable\n\
+             % Magnitude, in dB\n\\addplot table[x=f, y=db] {\\bodetable};\n\
+             % Phase, in degrees\n\\addplot table[x=f, y=phase] {\\bodetable};\n"
+        );
+    }
+}
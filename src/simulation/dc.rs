@@ -0,0 +1,190 @@
+use bevy::utils::HashMap;
+
+use crate::graph::netlist::{NetComponent, NetId, Netlist};
+use crate::structs::{
+    export_value, Annotation, AnnotationKind, Info, Quantity, TikzComponent, ValueStyle,
+};
+
+use super::linear::{elements, solve_nodal, Element, Solution};
+use super::AnalysisError;
+
+/// Value of a resistor, which must be positive, since the solver divides by it.
+pub(super) fn resistance(component: &NetComponent) -> Result<f64, AnalysisError> {
+    let record = &component.record;
+    match record.info.value {
+        Some(value) if value > 0. => Ok(value),
+        Some(_) => Err(AnalysisError::InvalidResistance(record.label.clone())),
+        None => Err(AnalysisError::MissingValue(record.label.clone())),
+    }
+}
+
+/// How a component behaves at DC, or `None` if it is not linear.
+fn model(component: &NetComponent) -> Result<Option<Element<f64>>, AnalysisError> {
+    use TikzComponent::*;
+    let record = &component.record;
+    let value = || {
+        record
+            .info
+            .value
+            .ok_or_else(|| AnalysisError::MissingValue(record.label.clone()))
+    };
+    Ok(Some(match record.kind {
+        Resistor | VariableResistor => Element::Admittance(1. / resistance(component)?),
+        // A lamp without a value is a load too light to matter.
+        Lamp if record.info.value.is_none() => Element::Admittance(0.),
+        Lamp => Element::Admittance(1. / resistance(component)?),
+        Capacitor | Switch | Voltmeter | SinISource => Element::Open,
+        Inductor | ClosedSwitch | Fuse | Ammeter | SinVSource => Element::Voltage(0.),
+        VSource | Battery => Element::Voltage(value()?),
+        ISource => Element::Current(value()?),
+        _ => return Ok(None),
+    }))
+}
+
+/// Node voltages and branch currents of the operating point.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DcSolution {
+    /// Voltage of each net, indexed like [`Netlist::nets`].
+    pub voltages: Vec<f64>,
+    /// Current through each two terminal component, from its start to its end, by its index in
+    /// [`Netlist::components`].
+    pub currents: HashMap<usize, f64>,
+    /// Labels of the components that were left out, since they are not linear.
+    pub skipped: Vec<String>,
+}
+
+impl DcSolution {
+    pub fn voltage(&self, net: NetId) -> f64 {
+        self.voltages[net.0]
+    }
+}
+
+/// Finds the DC operating point with modified nodal analysis. Resistors are conductances,
+/// inductors and closed switches shorts, capacitors and open switches opens. AC sources are off,
+/// which leaves shorts for voltage sources and opens for current sources.
+pub fn solve_dc(netlist: &Netlist) -> Result<DcSolution, AnalysisError> {
+    let ground = netlist.ground().ok_or(AnalysisError::NoGround)?;
+    let (elements, skipped) = elements(netlist, model)?;
+    let Solution { voltages, currents } =
+        solve_nodal(netlist, ground, &elements).ok_or(AnalysisError::Singular)?;
+    Ok(DcSolution {
+        voltages,
+        currents,
+        skipped,
+    })
+}
+
+/// Info of every bipole of the solution, by the index of its record, with the voltage across it
+/// and the current through it written into its `v=` and `i=` annotations. Annotations keep where
+/// they were placed, and reversed ones get the opposite sign.
+pub fn annotated(
+    netlist: &Netlist, solution: &DcSolution, value_style: ValueStyle,
+) -> Vec<(usize, Info)> {
+    let mut currents: Vec<_> = solution.currents.iter().collect();
+    currents.sort_by_key(|(index, _)| **index);
+    currents
+        .into_iter()
+        .map(|(index, current)| {
+            let component = &netlist.components[*index];
+            let [start, end] = [0, 1].map(|i| solution.voltage(component.terminals[i].net));
+            let mut info = component.record.info.clone();
+            // Circuitikz puts the positive sign at the start of the bipole.
+            for (kind, value, quantity) in [
+                (AnnotationKind::Voltage, start - end, Quantity::Voltage),
+                (AnnotationKind::Current, *current, Quantity::Current),
+            ] {
+                match info
+                    .annotations
+                    .iter_mut()
+                    .find(|annotation| annotation.kind == kind)
+                {
+                    Some(annotation) => {
+                        let value = if annotation.reversed() { -value } else { value };
+                        annotation.text = export_value(value, quantity, value_style);
+                    }
+                    None => info.annotations.push(Annotation {
+                        kind,
+                        modifiers: String::new(),
+                        text: export_value(value, quantity, value_style),
+                    }),
+                }
+            }
+            (component.index, info)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::fixtures::{bipole, divider, valued};
+    use crate::project::ComponentRecord;
+
+    /// 10 V across `upper` and 2 kΩ in series, with a 1 µF capacitor from the middle.
+    fn circuit(upper: f64) -> Vec<ComponentRecord> {
+        use TikzComponent::*;
+        let mut records = divider((VSource, 10.), (Resistor, upper), (Resistor, 2e3));
+        records.push(valued(1e-6, bipole(Capacitor, "C1", [64., 64.], [128., 64.])));
+        records
+    }
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!((value - expected).abs() < 1e-6, "{value} is not {expected}");
+    }
+
+    #[test]
+    fn divider_from_records() {
+        let netlist = Netlist::build(&circuit(1e3));
+        let solution = solve_dc(&netlist).unwrap();
+        assert!(solution.skipped.is_empty());
+        let voltage = |reference| solution.voltage(netlist.resolve(reference).unwrap());
+        assert_close(voltage("V1.start"), 0.);
+        assert_close(voltage("Z1.start"), 10.);
+        assert_close(voltage("Z2.start"), 20. / 3.);
+        // Components 1 to 3 are the source and the resistors, and 5 the capacitor, which is open.
+        for index in 1..=3 {
+            assert_close(solution.currents[&index] * 1e3, 10. / 3.);
+        }
+        assert_close(solution.currents[&5], 0.);
+    }
+
+    #[test]
+    fn annotates_voltages_and_currents() {
+        let mut records = circuit(1e3);
+        // Written before, against the bipole.
+        records[2].info.annotations.push(Annotation {
+            kind: AnnotationKind::Voltage,
+            modifiers: "^<".to_string(),
+            text: "V_1".to_string(),
+        });
+        let netlist = Netlist::build(&records);
+        let solution = solve_dc(&netlist).unwrap();
+        let annotated = annotated(&netlist, &solution, ValueStyle::Siunitx);
+        let indexes: Vec<usize> = annotated.iter().map(|(index, _)| *index).collect();
+        assert_eq!(indexes, [1, 2, 3, 5]);
+
+        let options = |index: usize| {
+            let (_, info) = annotated.iter().find(|(i, _)| *i == index).unwrap();
+            info.annotations
+                .iter()
+                .map(|annotation| annotation.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(options(2), ["v^<=-3.333<\\volt>", "i=3.333<\\milli\\ampere>"]);
+        assert_eq!(options(3), ["v=6.667<\\volt>", "i=3.333<\\milli\\ampere>"]);
+        // Through the source, the current goes from its negative end to its positive one.
+        assert_eq!(options(1), ["v=-10<\\volt>", "i=3.333<\\milli\\ampere>"]);
+    }
+
+    #[test]
+    fn resistances_must_be_positive() {
+        for value in [0., -1e3] {
+            let netlist = Netlist::build(&circuit(value));
+            assert_eq!(solve_dc(&netlist), Err(AnalysisError::InvalidResistance("Z1".to_string())));
+        }
+        let mut records = circuit(1e3);
+        records[2].info.value = None;
+        let netlist = Netlist::build(&records);
+        assert_eq!(solve_dc(&netlist), Err(AnalysisError::MissingValue("Z1".to_string())));
+    }
+}
@@ -0,0 +1,367 @@
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+use bevy::utils::HashMap;
+
+use crate::graph::netlist::{NetComponent, NetId, Netlist};
+use crate::structs::TikzComponent;
+
+use super::AnalysisError;
+
+/// Numbers the nodal equations are written in: real for DC, complex for AC.
+pub trait Scalar:
+    Copy
+    + Default
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + From<f64>
+{
+    fn norm(self) -> f64;
+}
+
+impl Scalar for f64 {
+    fn norm(self) -> f64 {
+        self.abs()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Complex {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl Complex {
+    pub const fn new(re: f64, im: f64) -> Self {
+        Self { re, im }
+    }
+
+    /// Angle in radians.
+    pub fn arg(self) -> f64 {
+        self.im.atan2(self.re)
+    }
+}
+
+impl Scalar for Complex {
+    fn norm(self) -> f64 {
+        self.re.hypot(self.im)
+    }
+}
+
+impl From<f64> for Complex {
+    fn from(re: f64) -> Self {
+        Self::new(re, 0.)
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self::new(self.re * other.re - self.im * other.im, self.re * other.im + self.im * other.re)
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        let denominator = other.re * other.re + other.im * other.im;
+        Self::new(
+            (self.re * other.re + self.im * other.im) / denominator,
+            (self.im * other.re - self.re * other.im) / denominator,
+        )
+    }
+}
+
+impl Neg for Complex {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self::new(-self.re, -self.im)
+    }
+}
+
+impl AddAssign for Complex {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Complex {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+/// Solves the linear system with Gaussian elimination and partial pivoting.
+fn solve<T: Scalar>(mut a: Vec<Vec<T>>, mut b: Vec<T>) -> Option<Vec<T>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].norm().total_cmp(&a[j][col].norm()))?;
+        if a[pivot][col].norm() < 1e-18 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let (done, rest) = a.split_at_mut(col + 1);
+        let pivot_row = &done[col];
+        for (offset, row) in rest.iter_mut().enumerate() {
+            let factor = row[col] / pivot_row[col];
+            for (value, &pivot) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *value -= factor * pivot;
+            }
+            let pivot = b[col];
+            b[col + 1 + offset] -= factor * pivot;
+        }
+    }
+    let mut x = vec![T::default(); n];
+    for row in (0..n).rev() {
+        let sum = a[row][row + 1..]
+            .iter()
+            .zip(&x[row + 1..])
+            .fold(T::default(), |sum, (&a, &x)| sum + a * x);
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+/// How a two terminal component enters the nodal equations.
+pub enum Element<T> {
+    Admittance(T),
+    /// A voltage source, positive at the end. Shorts are 0 V sources, so their current is known.
+    Voltage(T),
+    /// A current source, pushing current from the start to the end.
+    Current(T),
+    Open,
+}
+
+/// Elements of every two terminal component, by their index in [`Netlist::components`], with the
+/// labels of the components `model` has none for.
+pub fn elements<T>(
+    netlist: &Netlist, model: impl Fn(&NetComponent) -> Result<Option<Element<T>>, AnalysisError>,
+) -> Result<(Vec<(usize, Element<T>)>, Vec<String>), AnalysisError> {
+    let mut elements = Vec::new();
+    let mut skipped = Vec::new();
+    for (index, component) in netlist.components.iter().enumerate() {
+        if component.terminals.len() != 2 {
+            if component.record.kind != TikzComponent::Ground {
+                skipped.push(component.record.label.clone());
+            }
+            continue;
+        }
+        match model(component)? {
+            Some(element) => elements.push((index, element)),
+            None => skipped.push(component.record.label.clone()),
+        }
+    }
+    Ok((elements, skipped))
+}
+
+/// Conductance from every net to ground, so nets only reached through opens still solve.
+const GMIN: f64 = 1e-12;
+
+/// Voltages of every net and currents through every element, from its start to its end.
+pub struct Solution<T> {
+    pub voltages: Vec<T>,
+    pub currents: HashMap<usize, T>,
+}
+
+/// Writes and solves the modified nodal equations of the elements, given by the index of their
+/// component in [`Netlist::components`]. Unknowns are the voltage of every net but ground, then
+/// the current of every voltage source.
+pub fn solve_nodal<T: Scalar>(
+    netlist: &Netlist, ground: NetId, elements: &[(usize, Element<T>)],
+) -> Option<Solution<T>> {
+    let mut unknowns: Vec<Option<usize>> = Vec::new();
+    let mut count = 0;
+    for i in 0..netlist.nets.len() {
+        unknowns.push((NetId(i) != ground).then(|| {
+            count += 1;
+            count - 1
+        }));
+    }
+    let sources = elements
+        .iter()
+        .filter(|(_, element)| matches!(element, Element::Voltage(_)))
+        .count();
+    let size = count + sources;
+
+    let mut a = vec![vec![T::default(); size]; size];
+    let mut b = vec![T::default(); size];
+    for (i, row) in a.iter_mut().enumerate().take(count) {
+        row[i] += T::from(GMIN);
+    }
+    let mut branch = count;
+    let mut branches = Vec::new();
+    for (index, element) in elements {
+        let component = &netlist.components[*index];
+        let [start, end] = [0, 1].map(|i| unknowns[component.terminals[i].net.0]);
+        match *element {
+            Element::Admittance(y) => {
+                for (p, q) in [(start, end), (end, start)] {
+                    if let Some(p) = p {
+                        a[p][p] += y;
+                        if let Some(q) = q {
+                            a[p][q] -= y;
+                        }
+                    }
+                }
+            }
+            // Rows of the nets hold the currents leaving them.
+            Element::Voltage(v) => {
+                if let Some(start) = start {
+                    a[start][branch] += T::from(1.);
+                    a[branch][start] -= T::from(1.);
+                }
+                if let Some(end) = end {
+                    a[end][branch] -= T::from(1.);
+                    a[branch][end] += T::from(1.);
+                }
+                b[branch] = v;
+                branches.push((*index, branch));
+                branch += 1;
+            }
+            Element::Current(i) => {
+                if let Some(start) = start {
+                    b[start] -= i;
+                }
+                if let Some(end) = end {
+                    b[end] += i;
+                }
+            }
+            Element::Open => {}
+        }
+    }
+
+    let x = solve(a, b)?;
+    let voltages: Vec<T> = unknowns
+        .iter()
+        .map(|unknown| unknown.map_or(T::default(), |i| x[i]))
+        .collect();
+    let mut currents: HashMap<usize, T> = branches
+        .into_iter()
+        .map(|(index, branch)| (index, x[branch]))
+        .collect();
+    for (index, element) in elements {
+        let component = &netlist.components[*index];
+        let [start, end] = [0, 1].map(|i| voltages[component.terminals[i].net.0]);
+        match *element {
+            Element::Admittance(y) => currents.insert(*index, (start - end) * y),
+            Element::Current(i) => currents.insert(*index, i),
+            Element::Open => currents.insert(*index, T::default()),
+            Element::Voltage(_) => None,
+        };
+    }
+    Some(Solution { voltages, currents })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::fixtures::{bipole, divider, ground};
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!((value - expected).abs() < 1e-6, "{value} is not {expected}");
+    }
+
+    #[test]
+    fn complex_arithmetic() {
+        let a = Complex::new(1., 2.);
+        let b = Complex::new(3., -1.);
+        assert_eq!(a * b, Complex::new(5., 5.));
+        assert_eq!((a * b) / b, a);
+        assert_eq!(a - b + b, a);
+        assert_close(Complex::new(0., 2.).arg(), std::f64::consts::FRAC_PI_2);
+        assert_close(Complex::new(3., 4.).norm(), 5.);
+    }
+
+    #[test]
+    fn gaussian_elimination() {
+        // The first pivot is zero, so the rows are swapped.
+        let x = solve(vec![vec![0., 1.], vec![2., 1.]], vec![3., 7.]).unwrap();
+        assert_close(x[0], 2.);
+        assert_close(x[1], 3.);
+        assert!(solve(vec![vec![1., 2.], vec![2., 4.]], vec![1., 2.]).is_none());
+    }
+
+    #[test]
+    fn resistor_divider() {
+        use TikzComponent::*;
+        // The values are the ones of the elements below.
+        let netlist = Netlist::build(&divider((VSource, 10.), (Resistor, 1e3), (Resistor, 2e3)));
+        let (elements, skipped) = elements(&netlist, |component| {
+            Ok(match component.record.kind {
+                VSource => Some(Element::Voltage(10.)),
+                Resistor if component.record.label == "Z1" => Some(Element::Admittance(1e-3)),
+                Resistor => Some(Element::Admittance(0.5e-3)),
+                _ => None,
+            })
+        })
+        .unwrap();
+        // Grounds have a single terminal and are left out quietly.
+        assert!(skipped.is_empty());
+
+        let solution = solve_nodal(&netlist, netlist.ground().unwrap(), &elements).unwrap();
+        let voltage = |reference| solution.voltages[netlist.resolve(reference).unwrap().0];
+        assert_close(voltage("V1.start"), 0.);
+        assert_close(voltage("Z1.start"), 10.);
+        assert_close(voltage("Z1.end"), 10. * 2. / 3.);
+
+        // Components 1, 2 and 3 are the source and the resistors.
+        for index in 1..=3 {
+            assert_close(solution.currents[&index] * 1e3, 10. / 3.);
+        }
+    }
+
+    #[test]
+    fn rc_divider_at_its_corner() {
+        use TikzComponent::*;
+        let netlist =
+            Netlist::build(&divider((SinVSource, 1.), (Resistor, 1e3), (Capacitor, 1e-3)));
+        let (elements, _) = elements(&netlist, |component| {
+            Ok(Some(match component.record.kind {
+                SinVSource => Element::Voltage(Complex::from(1.)),
+                Resistor => Element::Admittance(Complex::from(1e-3)),
+                // The capacitor has the same impedance as the resistor.
+                _ => Element::Admittance(Complex::new(0., 1e-3)),
+            }))
+        })
+        .unwrap();
+
+        let solution = solve_nodal(&netlist, netlist.ground().unwrap(), &elements).unwrap();
+        let output = solution.voltages[netlist.resolve("Z1.end").unwrap().0];
+        assert_close(output.norm(), std::f64::consts::FRAC_1_SQRT_2);
+        assert_close(output.arg(), -std::f64::consts::FRAC_PI_4);
+    }
+
+    #[test]
+    fn parallel_sources_are_singular() {
+        let netlist = Netlist::build(&[
+            ground([0., 0.]),
+            bipole(TikzComponent::VSource, "V1", [0., 0.], [0., 64.]),
+            bipole(TikzComponent::VSource, "V2", [0., 0.], [0., 64.]),
+        ]);
+        let (elements, _) = elements(&netlist, |component| {
+            let volts = if component.record.label == "V1" { 10. } else { 5. };
+            Ok(Some(Element::Voltage(volts)))
+        })
+        .unwrap();
+        assert!(solve_nodal(&netlist, netlist.ground().unwrap(), &elements).is_none());
+    }
+}
@@ -0,0 +1,151 @@
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::graph::netlist::segment_distance;
+use crate::project::{records, RecordQuery};
+use crate::structs::{ComponentStructure, Info, Position, TikzComponent};
+use crate::GRID_SIZE;
+
+use super::logic::LogicCircuit;
+
+/// Inputs set to 1 in the live simulation, by a position on their net, so they keep their level
+/// while the circuit is edited.
+#[derive(Resource, Debug, Default)]
+pub struct LogicInputs(pub HashSet<Position>);
+
+/// Colored wire or pin drawn over the circuit by the live simulation.
+#[derive(Component)]
+pub struct LogicMark;
+
+const HIGH: Color = Color::srgb(0.3, 0.9, 0.3);
+const LOW: Color = Color::srgb(0.85, 0.3, 0.3);
+
+fn level_color(level: bool) -> Color {
+    if level {
+        HIGH
+    } else {
+        LOW
+    }
+}
+
+/// Switches the input whose net is under `pos` between 0 and 1.
+#[derive(Event)]
+pub struct ToggleLogicInput {
+    pub pos: Vec2,
+}
+
+pub fn toggle_logic_input(
+    trigger: Trigger<ToggleLogicInput>, components: RecordQuery, mut inputs: ResMut<LogicInputs>,
+) {
+    let pos = trigger.event().pos;
+    let records = records(&components);
+    let circuit = match LogicCircuit::build(&records) {
+        Ok(circuit) => circuit,
+        Err(err) => {
+            warn!("Could not simulate the circuit: {err}");
+            return;
+        }
+    };
+    let netlist = &circuit.netlist;
+    let clicked = netlist.net_at(pos.into()).or_else(|| {
+        records.iter().find_map(|record| match (record.kind, record.structure) {
+            (TikzComponent::Line, ComponentStructure::To([initial, fin]))
+                if segment_distance(pos, initial, fin) < GRID_SIZE / 2. =>
+            {
+                netlist.net_at(initial.into())
+            }
+            _ => None,
+        })
+    });
+    let Some(signal) = circuit
+        .inputs
+        .iter()
+        .find(|signal| Some(signal.net) == clicked)
+    else {
+        return;
+    };
+
+    let positions = &netlist.net(signal.net).positions;
+    if positions.iter().any(|pos| inputs.0.contains(pos)) {
+        inputs.0.retain(|pos| !positions.contains(pos));
+    } else {
+        inputs.0.insert(positions[0]);
+    }
+}
+
+pub fn logic_overlay_outdated(
+    inputs: Res<LogicInputs>,
+    changed: Query<(), Or<(Changed<Info>, Changed<ComponentStructure>)>>,
+    removed: RemovedComponents<TikzComponent>,
+) -> bool {
+    inputs.is_changed() || !changed.is_empty() || !removed.is_empty()
+}
+
+/// Propagates the levels of the inputs through the gates and colors every wire and gate pin by
+/// the level of its net.
+pub fn update_logic_overlay(
+    mut commands: Commands, inputs: Res<LogicInputs>, components: RecordQuery,
+    marks: Query<Entity, With<LogicMark>>,
+) {
+    for mark in &marks {
+        commands.entity(mark).despawn_recursive();
+    }
+    let records = records(&components);
+    let circuit = match LogicCircuit::build(&records) {
+        Ok(circuit) => circuit,
+        Err(err) => {
+            warn!("Could not simulate the circuit: {err}");
+            return;
+        }
+    };
+    let netlist = &circuit.netlist;
+    let levels: Vec<bool> = circuit
+        .inputs
+        .iter()
+        .map(|signal| {
+            let positions = &netlist.net(signal.net).positions;
+            positions.iter().any(|pos| inputs.0.contains(pos))
+        })
+        .collect();
+    let levels = circuit.evaluate(&levels);
+
+    for record in &records {
+        let ComponentStructure::To([initial, fin]) = record.structure else {
+            continue;
+        };
+        if record.kind != TikzComponent::Line {
+            continue;
+        }
+        let Some(level) = netlist.net_at(initial.into()).and_then(|net| levels[net.0]) else {
+            continue;
+        };
+        let delta = fin - initial;
+        commands.spawn((
+            Sprite::from_color(level_color(level), Vec2::ONE),
+            Transform::from_translation(initial.midpoint(fin).extend(5.))
+                .with_rotation(Quat::from_rotation_z(delta.to_angle()))
+                .with_scale(Vec3::new(delta.length(), 1.5, 1.)),
+            LogicMark,
+        ));
+    }
+    for component in &netlist.components {
+        if !component.record.kind.is_gate() {
+            continue;
+        }
+        for terminal in &component.terminals {
+            let Some(level) = levels[terminal.net.0] else {
+                continue;
+            };
+            commands.spawn((
+                Sprite::from_color(level_color(level), Vec2::splat(4.)),
+                Transform::from_translation(Vec2::from(terminal.position).extend(6.)),
+                LogicMark,
+            ));
+        }
+    }
+}
+
+pub fn clear_logic_overlay(mut commands: Commands, marks: Query<Entity, With<LogicMark>>) {
+    for mark in &marks {
+        commands.entity(mark).despawn_recursive();
+    }
+}
@@ -0,0 +1,365 @@
+use bevy::utils::HashMap;
+
+use crate::graph::netlist::{NetId, Netlist};
+use crate::project::ComponentRecord;
+use crate::structs::{ComponentStructure, Position, TikzComponent};
+
+/// Most inputs of a truth table, which has a row for each combination of them.
+pub const MAX_INPUTS: usize = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogicError {
+    NoGates,
+    /// A net driven by more than one gate output, with its name.
+    Conflict(String),
+    /// Gates whose outputs feed back into their inputs.
+    Loop,
+    TooManyInputs(usize),
+    /// A Karnaugh map is only drawn for 2 to 4 inputs.
+    NotMappable(usize),
+}
+
+impl std::fmt::Display for LogicError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoGates => write!(f, "The circuit has no gates"),
+            Self::Conflict(net) => write!(f, "{net} is driven by more than one gate"),
+            Self::Loop => write!(f, "The gates form a loop"),
+            Self::TooManyInputs(count) => {
+                write!(f, "{count} inputs are more than the {MAX_INPUTS} of a truth table")
+            }
+            Self::NotMappable(count) => {
+                write!(f, "A Karnaugh map takes 2 to 4 inputs, not {count}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LogicError {}
+
+/// Output of a gate for its inputs, if `kind` is a gate.
+pub fn gate_output(kind: TikzComponent, inputs: &[bool]) -> Option<bool> {
+    use TikzComponent::*;
+    let all = inputs.iter().all(|&input| input);
+    let any = inputs.iter().any(|&input| input);
+    let odd = inputs.iter().filter(|&&input| input).count() % 2 == 1;
+    Some(match kind {
+        AndGate => all,
+        NandGate => !all,
+        OrGate => any,
+        NorGate => !any,
+        XorGate => odd,
+        XnorGate => !odd,
+        NotGate => !inputs.first().copied().unwrap_or_default(),
+        _ => return None,
+    })
+}
+
+/// A named net the circuit is driven or read by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signal {
+    pub name: String,
+    pub net: NetId,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Gate {
+    kind: TikzComponent,
+    inputs: Vec<NetId>,
+    output: NetId,
+}
+
+/// The gates of a circuit, in an order where every gate comes after the gates it reads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogicCircuit {
+    pub netlist: Netlist,
+    gates: Vec<Gate>,
+    /// Nets read by a gate and driven by none.
+    pub inputs: Vec<Signal>,
+    /// Nets driven by a gate and read by none.
+    pub outputs: Vec<Signal>,
+}
+
+/// Label of a wire on the net, if any has one.
+fn wire_label(records: &[ComponentRecord], netlist: &Netlist, net: NetId) -> Option<String> {
+    records
+        .iter()
+        .find_map(|record| match (record.kind, record.structure) {
+            (TikzComponent::Line, ComponentStructure::To([initial, _]))
+                if netlist.net_at(Position::from(initial)) == Some(net)
+                    && !record.info.label.is_empty() =>
+            {
+                Some(record.info.label.clone())
+            }
+            _ => None,
+        })
+}
+
+impl LogicCircuit {
+    /// Finds the gates of `records` and how they are wired. Inputs are named after the label of a
+    /// wire on their net, or `A`, `B`, `C`... Outputs are named the same way, or `Y`, `Y2`...
+    pub fn build(records: &[ComponentRecord]) -> Result<Self, LogicError> {
+        let netlist = Netlist::build(records);
+        let mut gates = Vec::new();
+        for component in &netlist.components {
+            if gate_output(component.record.kind, &[]).is_none() {
+                continue;
+            }
+            let net = |pin: &str| component.terminal(pin).map(|terminal| terminal.net);
+            let inputs = ["in", "in 1", "in 2"].into_iter().filter_map(net).collect();
+            gates.push(Gate {
+                kind: component.record.kind,
+                inputs,
+                output: net("out").unwrap(),
+            });
+        }
+        if gates.is_empty() {
+            return Err(LogicError::NoGates);
+        }
+
+        let mut drivers: HashMap<NetId, usize> = HashMap::default();
+        for (i, gate) in gates.iter().enumerate() {
+            if drivers.insert(gate.output, i).is_some() {
+                return Err(LogicError::Conflict(netlist.net(gate.output).name.clone()));
+            }
+        }
+
+        // Kahn's algorithm, over the gates driving the inputs of each gate.
+        let mut waiting: Vec<usize> = gates
+            .iter()
+            .map(|gate| {
+                gate.inputs
+                    .iter()
+                    .filter(|&net| drivers.contains_key(net))
+                    .count()
+            })
+            .collect();
+        let mut ready: Vec<usize> = (0..gates.len()).filter(|&i| waiting[i] == 0).collect();
+        let mut order = Vec::new();
+        while let Some(i) = ready.pop() {
+            order.push(i);
+            for (j, gate) in gates.iter().enumerate() {
+                for &input in &gate.inputs {
+                    if input == gates[i].output {
+                        waiting[j] -= 1;
+                        if waiting[j] == 0 {
+                            ready.push(j);
+                        }
+                    }
+                }
+            }
+        }
+        if order.len() != gates.len() {
+            return Err(LogicError::Loop);
+        }
+        let gates: Vec<Gate> = order.into_iter().map(|i| gates[i].clone()).collect();
+
+        let mut input_nets: Vec<NetId> = gates
+            .iter()
+            .flat_map(|gate| gate.inputs.iter().copied())
+            .filter(|net| !drivers.contains_key(net))
+            .collect();
+        input_nets.sort();
+        input_nets.dedup();
+        let mut output_nets: Vec<NetId> = gates
+            .iter()
+            .map(|gate| gate.output)
+            .filter(|net| !gates.iter().any(|gate| gate.inputs.contains(net)))
+            .collect();
+        output_nets.sort();
+
+        let inputs = input_nets
+            .into_iter()
+            .enumerate()
+            .map(|(i, net)| Signal {
+                name: wire_label(records, &netlist, net)
+                    .unwrap_or_else(|| ((b'A' + (i % 26) as u8) as char).to_string()),
+                net,
+            })
+            .collect();
+        let outputs = output_nets
+            .into_iter()
+            .enumerate()
+            .map(|(i, net)| Signal {
+                name: wire_label(records, &netlist, net).unwrap_or_else(|| match i {
+                    0 => "Y".to_string(),
+                    i => format!("Y{}", i + 1),
+                }),
+                net,
+            })
+            .collect();
+
+        Ok(Self {
+            netlist,
+            gates,
+            inputs,
+            outputs,
+        })
+    }
+
+    /// Level of every net, indexed like [`Netlist::nets`], for the levels of the inputs. Nets no
+    /// gate touches have none.
+    pub fn evaluate(&self, inputs: &[bool]) -> Vec<Option<bool>> {
+        let mut levels = vec![None; self.netlist.nets.len()];
+        for (signal, &level) in self.inputs.iter().zip(inputs) {
+            levels[signal.net.0] = Some(level);
+        }
+        for gate in &self.gates {
+            let inputs: Vec<bool> = gate
+                .inputs
+                .iter()
+                .map(|net| levels[net.0].unwrap_or_default())
+                .collect();
+            levels[gate.output.0] = gate_output(gate.kind, &inputs);
+        }
+        levels
+    }
+
+    /// Outputs for every combination of the inputs, counting up in binary with the first input as
+    /// the most significant bit.
+    pub fn truth_table(&self) -> Result<TruthTable, LogicError> {
+        let count = self.inputs.len();
+        if count > MAX_INPUTS {
+            return Err(LogicError::TooManyInputs(count));
+        }
+        let rows = (0..1usize << count)
+            .map(|row| {
+                let inputs: Vec<bool> = (0..count)
+                    .map(|bit| row >> (count - 1 - bit) & 1 == 1)
+                    .collect();
+                let levels = self.evaluate(&inputs);
+                let outputs = self
+                    .outputs
+                    .iter()
+                    .map(|signal| levels[signal.net.0].unwrap_or_default())
+                    .collect();
+                (inputs, outputs)
+            })
+            .collect();
+        Ok(TruthTable {
+            inputs: self
+                .inputs
+                .iter()
+                .map(|signal| signal.name.clone())
+                .collect(),
+            outputs: self
+                .outputs
+                .iter()
+                .map(|signal| signal.name.clone())
+                .collect(),
+            rows,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TruthTable {
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    /// Levels of the inputs and of the outputs in each row.
+    pub rows: Vec<(Vec<bool>, Vec<bool>)>,
+}
+
+impl TruthTable {
+    /// Writes the table as a LaTeX `tabular`, with a rule between the inputs and the outputs.
+    pub fn to_latex(&self) -> String {
+        let bit = |level: &bool| if *level { "1" } else { "0" };
+        let mut text = format!(
+            "\\begin{{tabular}}{{{}|{}}}\n",
+            "c".repeat(self.inputs.len()),
+            "c".repeat(self.outputs.len())
+        );
+        let header: Vec<String> = self
+            .inputs
+            .iter()
+            .chain(&self.outputs)
+            .map(|name| format!("${name}$"))
+            .collect();
+        text.push_str(&format!("{} \\\\\n\\hline\n", header.join(" & ")));
+        for (inputs, outputs) in &self.rows {
+            let row: Vec<&str> = inputs.iter().chain(outputs).map(bit).collect();
+            text.push_str(&format!("{} \\\\\n", row.join(" & ")));
+        }
+        text.push_str("\\end{tabular}\n");
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::fixtures::{bipole, node, wire};
+    use crate::structs::Info;
+
+    fn outputs(table: &TruthTable) -> Vec<bool> {
+        table.rows.iter().map(|(_, outputs)| outputs[0]).collect()
+    }
+
+    #[test]
+    fn gates() {
+        use TikzComponent::*;
+        assert_eq!(gate_output(AndGate, &[true, false]), Some(false));
+        assert_eq!(gate_output(NandGate, &[true, false]), Some(true));
+        assert_eq!(gate_output(NorGate, &[false, false]), Some(true));
+        assert_eq!(gate_output(XorGate, &[true, true, true]), Some(true));
+        assert_eq!(gate_output(XnorGate, &[true, false]), Some(false));
+        assert_eq!(gate_output(NotGate, &[true]), Some(false));
+        assert_eq!(gate_output(Resistor, &[true]), None);
+    }
+
+    #[test]
+    fn truth_table_of_a_gate() {
+        let circuit = LogicCircuit::build(&[node(TikzComponent::AndGate, "", [0., 0.])]).unwrap();
+        let table = circuit.truth_table().unwrap();
+        assert_eq!(table.inputs, ["A", "B"]);
+        assert_eq!(table.outputs, ["Y"]);
+        let inputs: Vec<&[bool]> = table.rows.iter().map(|(inputs, _)| &inputs[..]).collect();
+        assert_eq!(
+            inputs,
+            [[false, false], [false, true], [true, false], [true, true]]
+        );
+        assert_eq!(outputs(&table), [false, false, false, true]);
+        assert_eq!(
+            table.to_latex(),
+            "\\begin{tabular}{cc|c}\n$A$ & $B$ & $Y$ \\\\\n\\hline\n0 & 0 & 0 \\\\\n\
+             0 & 1 & 0 \\\\\n1 & 0 & 0 \\\\\n1 & 1 & 1 \\\\\n\\end{tabular}\n"
+        );
+    }
+
+    #[test]
+    fn gates_are_evaluated_in_order() {
+        // The NOT is drawn first, but reads the AND. Its output wire names the output.
+        let records = [
+            node(TikzComponent::NotGate, "", [96., 0.]),
+            ComponentRecord {
+                info: Info::default().with_label("S".to_string()),
+                ..wire([128., 0.], [160., 0.])
+            },
+            wire([32., 0.], [64., 0.]),
+            node(TikzComponent::AndGate, "", [0., 0.]),
+        ];
+        let table = LogicCircuit::build(&records)
+            .and_then(|circuit| circuit.truth_table())
+            .unwrap();
+        assert_eq!(table.inputs, ["A", "B"]);
+        assert_eq!(table.outputs, ["S"]);
+        assert_eq!(outputs(&table), [true, true, true, false]);
+    }
+
+    #[test]
+    fn errors() {
+        let resistor = bipole(TikzComponent::Resistor, "", [0., 0.], [64., 0.]);
+        assert_eq!(LogicCircuit::build(&[resistor]), Err(LogicError::NoGates));
+
+        // Both outputs are wired together.
+        let records = [
+            node(TikzComponent::AndGate, "", [0., 0.]),
+            node(TikzComponent::OrGate, "", [0., 64.]),
+            wire([32., 0.], [32., 64.]),
+        ];
+        assert!(matches!(LogicCircuit::build(&records), Err(LogicError::Conflict(_))));
+
+        let records = [node(TikzComponent::NotGate, "", [0., 0.]), wire([32., 0.], [-32., 0.])];
+        assert_eq!(LogicCircuit::build(&records), Err(LogicError::Loop));
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::BTreeSet;
+
+use crate::import::expression::Expression;
+
+use super::logic::{LogicError, TruthTable};
+
+/// Most prime implicants left after the essential ones for which every cover is tried. The
+/// covers of more are picked greedily.
+const MAX_EXACT: usize = 12;
+
+/// A product of literals, as the levels of the inputs it reads. Bits follow the rows of a
+/// [`TruthTable`], with the first input as the most significant one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Implicant {
+    /// Level of every input the product reads. Bits of the other inputs are 0.
+    pub value: usize,
+    /// Inputs the product does not read.
+    pub free: usize,
+}
+
+impl Implicant {
+    pub fn covers(&self, minterm: usize) -> bool {
+        minterm & !self.free == self.value
+    }
+
+    pub fn literals(&self, count: usize) -> usize {
+        count - self.free.count_ones() as usize
+    }
+
+    /// Input index and level of each literal, in the order of the inputs.
+    pub fn literal_levels(&self, count: usize) -> impl Iterator<Item = (usize, bool)> + '_ {
+        (0..count)
+            .map(move |i| (i, 1 << (count - 1 - i)))
+            .filter(|&(_, bit)| self.free & bit == 0)
+            .map(|(i, bit)| (i, self.value & bit != 0))
+    }
+}
+
+/// Prime implicants of the function that is 1 on `minterms`, by merging products that differ in
+/// a single input until none merge, as Quine and McCluskey do.
+pub fn prime_implicants(minterms: &[usize]) -> Vec<Implicant> {
+    let mut current: BTreeSet<Implicant> = minterms
+        .iter()
+        .map(|&value| Implicant { value, free: 0 })
+        .collect();
+    let mut primes = BTreeSet::new();
+    while !current.is_empty() {
+        let mut merged = BTreeSet::new();
+        let mut used = BTreeSet::new();
+        for a in &current {
+            for b in current.range(a..).skip(1) {
+                let difference = a.value ^ b.value;
+                if a.free == b.free && difference.count_ones() == 1 {
+                    merged.insert(Implicant {
+                        value: a.value & b.value,
+                        free: a.free | difference,
+                    });
+                    used.insert(*a);
+                    used.insert(*b);
+                }
+            }
+        }
+        primes.extend(current.difference(&used).copied());
+        current = merged;
+    }
+    primes.into_iter().collect()
+}
+
+/// Fewest prime implicants covering `minterms` of a function of `count` inputs, with the fewest
+/// literals among covers of the same size. The essential primes are taken first.
+pub fn minimize(minterms: &[usize], count: usize) -> Vec<Implicant> {
+    let primes = prime_implicants(minterms);
+    let mut chosen: BTreeSet<Implicant> = BTreeSet::new();
+    for &minterm in minterms {
+        let mut covering = primes.iter().filter(|prime| prime.covers(minterm));
+        if let (Some(&prime), None) = (covering.next(), covering.next()) {
+            chosen.insert(prime);
+        }
+    }
+
+    let mut left: Vec<usize> = minterms
+        .iter()
+        .copied()
+        .filter(|&minterm| !chosen.iter().any(|prime| prime.covers(minterm)))
+        .collect();
+    let candidates: Vec<Implicant> = primes
+        .into_iter()
+        .filter(|prime| !chosen.contains(prime) && left.iter().any(|&m| prime.covers(m)))
+        .collect();
+
+    if candidates.len() <= MAX_EXACT {
+        let cost = |set: usize| {
+            let literals: usize = (0..candidates.len())
+                .filter(|i| set >> i & 1 == 1)
+                .map(|i| candidates[i].literals(count))
+                .sum();
+            (set.count_ones(), literals)
+        };
+        let best = (0..1usize << candidates.len())
+            .filter(|set| {
+                left.iter().all(|&minterm| {
+                    (0..candidates.len())
+                        .any(|i| set >> i & 1 == 1 && candidates[i].covers(minterm))
+                })
+            })
+            .min_by_key(|&set| cost(set))
+            .unwrap_or_default();
+        chosen.extend(
+            (0..candidates.len())
+                .filter(|i| best >> i & 1 == 1)
+                .map(|i| candidates[i]),
+        );
+    } else {
+        while !left.is_empty() {
+            let &prime = candidates
+                .iter()
+                .max_by_key(|prime| {
+                    let covered = left.iter().filter(|&&m| prime.covers(m)).count();
+                    (covered, std::cmp::Reverse(prime.literals(count)))
+                })
+                .unwrap();
+            left.retain(|&minterm| !prime.covers(minterm));
+            chosen.insert(prime);
+        }
+    }
+    chosen.into_iter().rev().collect()
+}
+
+/// Minimal sum of products of an output of the table.
+pub fn minimize_output(table: &TruthTable, output: usize) -> Vec<Implicant> {
+    let minterms: Vec<usize> = table
+        .rows
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, outputs))| outputs[output])
+        .map(|(row, _)| row)
+        .collect();
+    minimize(&minterms, table.inputs.len())
+}
+
+/// Sum of products as gates, or `None` for a constant function.
+pub fn to_expression(implicants: &[Implicant], inputs: &[String]) -> Option<Expression> {
+    let and = |a, b| Expression::Gate(crate::TikzComponent::AndGate, Box::new(a), Box::new(b));
+    let or = |a, b| Expression::Gate(crate::TikzComponent::OrGate, Box::new(a), Box::new(b));
+    implicants
+        .iter()
+        .map(|implicant| {
+            implicant
+                .literal_levels(inputs.len())
+                .map(|(i, level)| {
+                    let input = Expression::Input(inputs[i].clone());
+                    if level {
+                        input
+                    } else {
+                        Expression::Not(Box::new(input))
+                    }
+                })
+                .reduce(and)
+        })
+        .collect::<Option<Vec<_>>>()?
+        .into_iter()
+        .reduce(or)
+}
+
+/// Sum of products in math mode, like `A\,\overline{B} + C`.
+pub fn latex_sum(implicants: &[Implicant], inputs: &[String]) -> String {
+    if implicants.is_empty() {
+        return "0".to_string();
+    }
+    let products: Vec<String> = implicants
+        .iter()
+        .map(|implicant| {
+            let literals: Vec<String> = implicant
+                .literal_levels(inputs.len())
+                .map(|(i, level)| {
+                    if level {
+                        inputs[i].clone()
+                    } else {
+                        format!("\\overline{{{}}}", inputs[i])
+                    }
+                })
+                .collect();
+            if literals.is_empty() {
+                "1".to_string()
+            } else {
+                literals.join("\\,")
+            }
+        })
+        .collect();
+    products.join(" + ")
+}
+
+/// Gray code of `bits` bits, so neighbouring cells of a Karnaugh map differ in one input.
+fn gray(bits: usize) -> Vec<usize> {
+    (0..1usize << bits).map(|i| i ^ (i >> 1)).collect()
+}
+
+/// Writes a Karnaugh map of each output as a LaTeX `figure`, with the minimal sum of products as
+/// its caption. The first half of the inputs index the rows and the others the columns.
+pub fn karnaugh_latex(table: &TruthTable) -> Result<String, LogicError> {
+    let count = table.inputs.len();
+    if !(2..=4).contains(&count) {
+        return Err(LogicError::NotMappable(count));
+    }
+    let row_bits = count / 2;
+    let column_bits = count - row_bits;
+    let code = |value: usize, bits: usize| format!("{value:0bits$b}");
+
+    let mut text = String::new();
+    for (output, name) in table.outputs.iter().enumerate() {
+        text.push_str("\\begin{figure}[h]\n\\centering\n");
+        text.push_str(&format!("\\begin{{tabular}}{{c|{}}}\n", "c".repeat(1 << column_bits)));
+        let columns: Vec<String> = gray(column_bits)
+            .into_iter()
+            .map(|column| code(column, column_bits))
+            .collect();
+        text.push_str(&format!(
+            "${} \\backslash {}$ & {} \\\\\n\\hline\n",
+            table.inputs[..row_bits].join("\\,"),
+            table.inputs[row_bits..].join("\\,"),
+            columns.join(" & ")
+        ));
+        for row in gray(row_bits) {
+            let cells: Vec<&str> = gray(column_bits)
+                .into_iter()
+                .map(|column| {
+                    if table.rows[row << column_bits | column].1[output] {
+                        "1"
+                    } else {
+                        "0"
+                    }
+                })
+                .collect();
+            text.push_str(&format!("{} & {} \\\\\n", code(row, row_bits), cells.join(" & ")));
+        }
+        text.push_str("\\end{tabular}\n");
+        let sum = latex_sum(&minimize_output(table, output), &table.inputs);
+        text.push_str(&format!("\\caption{{${name} = {sum}$}}\n\\end{{figure}}\n"));
+    }
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Table of a function of `count` inputs named `A`, `B`... that is 1 on `minterms`.
+    fn table(count: usize, minterms: &[usize]) -> TruthTable {
+        TruthTable {
+            inputs: (0..count)
+                .map(|i| ((b'A' + i as u8) as char).to_string())
+                .collect(),
+            outputs: vec!["Y".to_string()],
+            rows: (0..1usize << count)
+                .map(|row| {
+                    let inputs = (0..count).map(|bit| row >> (count - 1 - bit) & 1 == 1);
+                    (inputs.collect(), vec![minterms.contains(&row)])
+                })
+                .collect(),
+        }
+    }
+
+    fn sum(count: usize, minterms: &[usize]) -> String {
+        let table = table(count, minterms);
+        latex_sum(&minimize_output(&table, 0), &table.inputs)
+    }
+
+    #[test]
+    fn implicants() {
+        let product = Implicant {
+            value: 0b100,
+            free: 0b001,
+        };
+        assert!(product.covers(0b100) && product.covers(0b101));
+        assert!(!product.covers(0b110));
+        assert_eq!(product.literals(3), 2);
+        assert_eq!(product.literal_levels(3).collect::<Vec<_>>(), [(0, true), (1, false)]);
+        // Majority of three inputs.
+        assert_eq!(prime_implicants(&[3, 5, 6, 7]).len(), 3);
+    }
+
+    #[test]
+    fn karnaugh_map_corners_and_center() {
+        // Y = Σ(0, 2, 5, 7, 8, 10, 13, 15), the four corners and the middle square of the map.
+        assert_eq!(
+            sum(4, &[0, 2, 5, 7, 8, 10, 13, 15]),
+            "B\\,D + \\overline{B}\\,\\overline{D}"
+        );
+        // Redundant primes are left out: A B + !A C needs no B C.
+        assert_eq!(sum(3, &[1, 3, 6, 7]), "A\\,B + \\overline{A}\\,C");
+        assert_eq!(sum(2, &[]), "0");
+        assert_eq!(sum(2, &[0, 1, 2, 3]), "1");
+    }
+
+    #[test]
+    fn single_literal() {
+        let inputs = ["A".to_string(), "B".to_string()];
+        // A B + A !B
+        let implicants = minimize(&[2, 3], 2);
+        assert_eq!(latex_sum(&implicants, &inputs), "A");
+        assert_eq!(to_expression(&implicants, &inputs), Some(Expression::Input("A".to_string())));
+        let implicants = minimize(&[0, 2], 2);
+        assert_eq!(
+            to_expression(&implicants, &inputs),
+            Some(Expression::Not(Box::new(Expression::Input("B".to_string()))))
+        );
+        assert_eq!(to_expression(&minimize(&[], 2), &inputs), None);
+    }
+
+    #[test]
+    fn karnaugh_latex_of_and() {
+        let text = karnaugh_latex(&table(2, &[3])).unwrap();
+        assert!(text.contains("$A \\backslash B$ & 0 & 1 \\\\\n"));
+        assert!(text.contains("0 & 0 & 0 \\\\\n1 & 0 & 1 \\\\\n"));
+        assert!(text.contains("\\caption{$Y = A\\,B$}"));
+        assert_eq!(karnaugh_latex(&table(1, &[1])), Err(LogicError::NotMappable(1)));
+    }
+}
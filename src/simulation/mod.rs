@@ -0,0 +1,376 @@
+use bevy::prelude::*;
+
+use crate::export::sibling_path;
+use crate::graph::netlist::{NetId, Netlist};
+use crate::history::Checkpoint;
+use crate::import::expression::Expression;
+use crate::input::MouseMode;
+use crate::project::{entity_records, records, ProjectFile, RecordQuery, ReplaceCircuit};
+use crate::structs::{
+    format_value, parse_value, ComponentStructure, Info, Quantity, TikzComponent, ValueStyle,
+};
+use crate::{GRID_SIZE, TEXT_SCALE};
+
+pub mod ac;
+pub mod dc;
+pub mod linear;
+pub mod live;
+pub mod logic;
+pub mod minimize;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnalysisError {
+    NoGround,
+    /// A component that needs a value has none, with its label.
+    MissingValue(String),
+    /// A resistance that is zero or negative, with the label of its component.
+    InvalidResistance(String),
+    /// Loops of voltage sources, or nets left floating.
+    Singular,
+    /// No AC source drives the input of a sweep.
+    NoExcitation,
+    /// A node asked for that is not a net name nor a `label.pin` of the circuit.
+    UnknownNode(String),
+    InvalidFrequency(String),
+}
+
+impl std::fmt::Display for AnalysisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoGround => write!(f, "The circuit has no ground"),
+            Self::MissingValue(label) => write!(f, "{label} has no value"),
+            Self::InvalidResistance(label) => write!(f, "{label} needs a positive resistance"),
+            Self::Singular => write!(f, "The circuit has no single solution"),
+            Self::NoExcitation => write!(f, "No AC source drives the input"),
+            Self::UnknownNode(node) => write!(f, "Unknown node {node}"),
+            Self::InvalidFrequency(text) => write!(f, "Invalid frequency {text}"),
+        }
+    }
+}
+
+impl std::error::Error for AnalysisError {}
+
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DcOverlay::default())
+            .add_observer(annotate_dc)
+            .add_observer(export_bode)
+            .add_observer(export_truth_table)
+            .add_observer(export_karnaugh)
+            .add_observer(minimize_logic)
+            .insert_resource(live::LogicInputs::default())
+            .add_observer(live::toggle_logic_input)
+            .add_systems(
+                Update,
+                live::update_logic_overlay
+                    .run_if(in_state(MouseMode::Simulate))
+                    .run_if(live::logic_overlay_outdated.or(state_changed::<MouseMode>)),
+            )
+            .add_systems(OnExit(MouseMode::Simulate), live::clear_logic_overlay)
+            .add_systems(Update, update_dc_overlay.run_if(dc_overlay_outdated));
+    }
+}
+
+/// If the node voltages and branch currents are drawn over the circuit.
+#[derive(Resource, Debug, Default)]
+pub struct DcOverlay {
+    pub enabled: bool,
+}
+
+/// Text of a voltage or current drawn by the overlay.
+#[derive(Component)]
+pub struct DcOverlayMark;
+
+const COLOR: Color = Color::srgb(0.45, 0.9, 0.45);
+
+fn dc_overlay_outdated(
+    overlay: Res<DcOverlay>, changed: Query<(), Or<(Changed<Info>, Changed<ComponentStructure>)>>,
+    removed: RemovedComponents<TikzComponent>,
+) -> bool {
+    overlay.is_changed() || (overlay.enabled && (!changed.is_empty() || !removed.is_empty()))
+}
+
+/// Solves the circuit again and redraws the overlay, or clears it when disabled.
+fn update_dc_overlay(
+    mut commands: Commands, overlay: Res<DcOverlay>, components: RecordQuery,
+    marks: Query<Entity, With<DcOverlayMark>>,
+) {
+    for mark in &marks {
+        commands.entity(mark).despawn_recursive();
+    }
+    if !overlay.enabled {
+        return;
+    }
+
+    let (_, records): (Vec<_>, Vec<_>) = entity_records(&components).into_iter().unzip();
+    let netlist = Netlist::build(&records);
+    let solution = match dc::solve_dc(&netlist) {
+        Ok(solution) => solution,
+        Err(err) => {
+            warn!("Could not solve the circuit: {err}");
+            return;
+        }
+    };
+    for label in &solution.skipped {
+        warn!("Skipped {label}, which is not linear");
+    }
+
+    let mut text = |content: String, pos: Vec2| {
+        commands.spawn((
+            Text2d::new(content),
+            TextColor(COLOR),
+            Transform::from_translation(pos.extend(10.)).with_scale(Vec3::splat(TEXT_SCALE)),
+            DcOverlayMark,
+        ));
+    };
+    for (net, voltage) in netlist.nets.iter().zip(&solution.voltages) {
+        // Nets of a single point are the free ends of components.
+        if net.positions.len() < 2 && net.name != "0" {
+            continue;
+        }
+        let pos: Vec2 = net.positions[0].into();
+        text(format_value(*voltage, Quantity::Voltage), pos + Vec2::new(6., 6.));
+    }
+    for (index, current) in &solution.currents {
+        let component = &netlist.components[*index];
+        if let ComponentStructure::To([initial, fin]) = component.record.structure {
+            text(
+                format_value(*current, Quantity::Current),
+                initial.midpoint(fin) + Vec2::new(10., -10.),
+            );
+        }
+    }
+}
+
+/// Writes the voltage across every bipole and the current through it into its `v=` and `i=`
+/// annotations, with [`dc::annotated`].
+#[derive(Event)]
+pub struct AnnotateDc;
+
+fn annotate_dc(
+    _: Trigger<AnnotateDc>, mut commands: Commands, components: RecordQuery,
+    value_style: Res<ValueStyle>,
+) {
+    let (entities, records): (Vec<_>, Vec<_>) = entity_records(&components).into_iter().unzip();
+    let netlist = Netlist::build(&records);
+    let solution = match dc::solve_dc(&netlist) {
+        Ok(solution) => solution,
+        Err(err) => {
+            error!("Could not solve the circuit: {err}");
+            return;
+        }
+    };
+
+    commands.trigger(Checkpoint);
+    for (index, info) in dc::annotated(&netlist, &solution, *value_style) {
+        commands.entity(entities[index]).insert(info);
+    }
+}
+
+/// Points of a sweep in each decade of frequency.
+const POINTS_PER_DECADE: usize = 20;
+
+/// Reads the nodes and the frequencies of a sweep, written `input, output, start, stop`. The
+/// frequencies may be left out, to sweep from 1 Hz to 1 MHz.
+pub fn parse_sweep(
+    text: &str, netlist: &Netlist,
+) -> Result<(NetId, NetId, f64, f64), AnalysisError> {
+    let mut parts = text.split(',').map(str::trim);
+    let mut node = || {
+        let reference = parts.next().unwrap_or_default();
+        netlist
+            .resolve(reference)
+            .ok_or_else(|| AnalysisError::UnknownNode(reference.to_string()))
+    };
+    let (input, output) = (node()?, node()?);
+    let mut frequency = |default| match parts.next() {
+        None => Ok(default),
+        Some(text) => parse_value(text, Quantity::Frequency)
+            .ok()
+            .filter(|frequency| *frequency > 0.)
+            .ok_or_else(|| AnalysisError::InvalidFrequency(text.to_string())),
+    };
+    let (start, stop) = (frequency(1.)?, frequency(1e6)?);
+    if start >= stop {
+        return Err(AnalysisError::InvalidFrequency(format!("{start} >= {stop}")));
+    }
+    Ok((input, output, start, stop))
+}
+
+/// Sweeps the frequency between two nodes, written like in [`parse_sweep`], and writes the Bode
+/// plot beside the project file, with the `.bode.tex` extension.
+#[derive(Event)]
+pub struct ExportBode {
+    pub text: String,
+}
+
+fn export_bode(trigger: Trigger<ExportBode>, file: Res<ProjectFile>, components: RecordQuery) {
+    let netlist = Netlist::build(&records(&components));
+    let text = &trigger.event().text;
+    let points = parse_sweep(text, &netlist).and_then(|(input, output, start, stop)| {
+        ac::sweep(&netlist, input, output, start, stop, POINTS_PER_DECADE)
+    });
+    match points {
+        Ok(points) => {
+            let path = sibling_path(&file, "bode.tex");
+            match std::fs::write(&path, ac::pgfplots(&points, text)) {
+                Ok(()) => info!("Exported Bode plot to {path}"),
+                Err(err) => error!("Could not write {path}: {err}"),
+            }
+        }
+        Err(err) => error!("Could not sweep {text}: {err}"),
+    }
+}
+
+/// Writes the truth table of the gates beside the project file, with the `.truth.tex` extension.
+#[derive(Event)]
+pub struct ExportTruthTable;
+
+fn export_truth_table(
+    _: Trigger<ExportTruthTable>, file: Res<ProjectFile>, components: RecordQuery,
+) {
+    let table =
+        logic::LogicCircuit::build(&records(&components)).and_then(|circuit| circuit.truth_table());
+    match table {
+        Ok(table) => {
+            let path = sibling_path(&file, "truth.tex");
+            match std::fs::write(&path, table.to_latex()) {
+                Ok(()) => info!("Exported truth table to {path}"),
+                Err(err) => error!("Could not write {path}: {err}"),
+            }
+        }
+        Err(err) => error!("Could not build the truth table: {err}"),
+    }
+}
+
+/// Writes a Karnaugh map of each output beside the project file, with the `.kmap.tex` extension.
+#[derive(Event)]
+pub struct ExportKarnaugh;
+
+fn export_karnaugh(_: Trigger<ExportKarnaugh>, file: Res<ProjectFile>, components: RecordQuery) {
+    let text = logic::LogicCircuit::build(&records(&components))
+        .and_then(|circuit| circuit.truth_table())
+        .and_then(|table| minimize::karnaugh_latex(&table));
+    match text {
+        Ok(text) => {
+            let path = sibling_path(&file, "kmap.tex");
+            match std::fs::write(&path, text) {
+                Ok(()) => info!("Exported Karnaugh map to {path}"),
+                Err(err) => error!("Could not write {path}: {err}"),
+            }
+        }
+        Err(err) => error!("Could not build the Karnaugh map: {err}"),
+    }
+}
+
+/// Draws the minimal sum of products of the gates, in their place or to their right.
+#[derive(Event)]
+pub struct MinimizeLogic {
+    pub beside: bool,
+}
+
+fn minimize_logic(
+    trigger: Trigger<MinimizeLogic>, mut commands: Commands, components: RecordQuery,
+) {
+    let mut records = records(&components);
+    let table = match logic::LogicCircuit::build(&records).and_then(|circuit| circuit.truth_table())
+    {
+        Ok(table) => table,
+        Err(err) => {
+            error!("Could not minimize the circuit: {err}");
+            return;
+        }
+    };
+    let [output] = table.outputs.as_slice() else {
+        error!("Only circuits with a single output can be minimized");
+        return;
+    };
+    let implicants = minimize::minimize_output(&table, 0);
+    let Some(expression) = minimize::to_expression(&implicants, &table.inputs) else {
+        error!("{output} is constant and needs no gates");
+        return;
+    };
+    let minimal = match &expression {
+        Expression::Input(input) => Ok(crate::import::expression::draw_input(input, output)),
+        gates => crate::import::expression::draw(gates, output),
+    };
+    let minimal = match minimal {
+        Ok(minimal) => minimal,
+        Err(err) => {
+            error!("Could not draw the minimal circuit: {err}");
+            return;
+        }
+    };
+    info!("Minimized {output} = {}", minimize::latex_sum(&implicants, &table.inputs));
+
+    if trigger.event().beside {
+        // Four grid squares to the right of the circuit, with the tops lined up.
+        let terminals = records.iter().flat_map(|record| record.terminals());
+        let corner = terminals.fold(Vec2::NEG_INFINITY, Vec2::max);
+        let points: Vec<Vec2> = minimal
+            .iter()
+            .flat_map(|record| record.terminals())
+            .collect();
+        let left = points
+            .iter()
+            .fold(f32::INFINITY, |left, point| left.min(point.x));
+        let top = points
+            .iter()
+            .fold(f32::NEG_INFINITY, |top, point| top.max(point.y));
+        let offset = Vec2::new(corner.x + 4. * GRID_SIZE - left, corner.y - top);
+        let offset = (offset / GRID_SIZE).ceil() * GRID_SIZE;
+        records.extend(minimal.iter().map(|record| record.translated(offset)));
+    } else {
+        records = minimal;
+    }
+    commands.trigger(Checkpoint);
+    commands.trigger(ReplaceCircuit(records));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::fixtures::bipole;
+
+    fn netlist() -> Netlist {
+        Netlist::build(&[bipole(TikzComponent::Resistor, "R1", [0., 0.], [64., 0.])])
+    }
+
+    #[test]
+    fn sweep_ranges() {
+        let netlist = netlist();
+        let [start, end] = ["R1.start", "R1.end"].map(|node| netlist.resolve(node).unwrap());
+        assert_eq!(parse_sweep("R1.start, R1.end", &netlist), Ok((start, end, 1., 1e6)));
+        assert_eq!(
+            parse_sweep(" R1.end ,R1.start, 10, 1k ", &netlist),
+            Ok((end, start, 10., 1e3))
+        );
+        // Only the stop frequency left out.
+        assert_eq!(parse_sweep("R1.start, R1.end, 1k", &netlist), Ok((start, end, 1e3, 1e6)));
+    }
+
+    #[test]
+    fn bad_sweeps() {
+        let netlist = netlist();
+        let err = |text: &str| parse_sweep(text, &netlist).unwrap_err();
+        assert_eq!(err("R1.start, R2.end"), AnalysisError::UnknownNode("R2.end".to_string()));
+        assert_eq!(err("R1.start"), AnalysisError::UnknownNode(String::new()));
+        // A logarithmic sweep can reach neither zero nor a negative frequency.
+        for text in ["0", "-10", "abc"] {
+            assert_eq!(
+                err(&format!("R1.start, R1.end, {text}, 1k")),
+                AnalysisError::InvalidFrequency(text.to_string())
+            );
+        }
+        assert_eq!(
+            err("R1.start, R1.end, 1k, 10"),
+            AnalysisError::InvalidFrequency("1000 >= 10".to_string())
+        );
+        assert_eq!(
+            err("R1.start, R1.end, 1k, 1k"),
+            AnalysisError::InvalidFrequency("1000 >= 1000".to_string())
+        );
+    }
+}
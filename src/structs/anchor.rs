@@ -4,6 +4,7 @@ use bevy::{
 };
 
 use super::{BuildInfo, ComponentStructure};
+use crate::TEXT_SCALE;
 
 #[derive(Component)]
 #[component(on_insert = on_insert_hook)]
@@ -21,8 +22,25 @@ fn on_insert_hook(mut world: DeferredWorld, entity: Entity, _component: Componen
         ComponentStructure::Node(_) => ComponentStructure::Node(anchor),
         ComponentStructure::To(_) => ComponentStructure::To([anchor, other_pos]),
     };
+    let is_node = matches!(*structure, ComponentStructure::Node(_));
     let middle = anchor.midpoint(other_pos);
 
     let mut transform = world.get_mut::<Transform>(entity).unwrap();
     transform.translation = (middle, 0.0).into();
+    if !is_node {
+        return;
+    }
+
+    // Nodes take their orientation from the build info. The label, the first child, is mirrored
+    // back so the text stays readable.
+    let mirror = build_info.mirror_scale().extend(1.);
+    transform.rotation = Quat::from_rotation_z(build_info.angle);
+    transform.scale = mirror;
+
+    let Some(&label) = world.get::<Children>(entity).and_then(|children| children.first()) else {
+        return;
+    };
+    if let Some(mut transform) = world.get_mut::<Transform>(label) {
+        transform.scale = mirror * TEXT_SCALE;
+    }
 }
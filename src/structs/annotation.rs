@@ -0,0 +1,186 @@
+use serde::{Deserialize, Serialize};
+
+/// What a circuitikz annotation of a bipole shows, named by the letter of its key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnnotationKind {
+    /// `l`, a label that can be placed on either side.
+    Label,
+    /// `v`, the voltage across the bipole.
+    Voltage,
+    /// `i`, the current through it.
+    Current,
+    /// `f`, the flow arrow beside it.
+    Flow,
+    /// `a`, the text on the opposite side of the label.
+    Annotation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Below,
+    Above,
+}
+
+/// One `key=text` option of a bipole, like `v_>=V_1`, `i<=I_2` or `l^=R_1`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub kind: AnnotationKind,
+    /// Placement symbols after the letter, `_`/`^` for the side and `<`/`>` for the direction,
+    /// kept in the order they are written since circuitikz reads it.
+    pub modifiers: String,
+    pub text: String,
+}
+
+impl AnnotationKind {
+    fn letter(&self) -> char {
+        match self {
+            Self::Label => 'l',
+            Self::Voltage => 'v',
+            Self::Current => 'i',
+            Self::Flow => 'f',
+            Self::Annotation => 'a',
+        }
+    }
+
+    fn from_letter(letter: char) -> Option<Self> {
+        Some(match letter {
+            'l' => Self::Label,
+            'v' => Self::Voltage,
+            'i' => Self::Current,
+            'f' => Self::Flow,
+            'a' => Self::Annotation,
+            _ => return None,
+        })
+    }
+}
+
+impl Annotation {
+    /// Reads one option. Keys without placement symbols, like `l=`, are left to the caller, since
+    /// they are the plain label.
+    pub fn parse(option: &str) -> Option<Self> {
+        let (key, text) = option.split_once('=')?;
+        let mut key = key.trim().chars();
+        let kind = AnnotationKind::from_letter(key.next()?)?;
+        let modifiers: String = key.collect();
+        if !modifiers.chars().all(|c| matches!(c, '_' | '^' | '<' | '>')) {
+            return None;
+        }
+        if kind == AnnotationKind::Label && modifiers.is_empty() {
+            return None;
+        }
+        Some(Self {
+            kind,
+            modifiers,
+            text: text.trim().to_string(),
+        })
+    }
+
+    /// Reads every annotation of a comma separated list, skipping what is not one. Commas inside
+    /// braces are part of the text.
+    pub fn parse_list(options: &str) -> Vec<Self> {
+        let mut depth = 0;
+        options
+            .split(|c| {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                c == ',' && depth == 0
+            })
+            .filter_map(Self::parse)
+            .collect()
+    }
+
+    pub fn key(&self) -> String {
+        format!("{}{}", self.kind.letter(), self.modifiers)
+    }
+
+    /// Side of the bipole it is drawn on. Labels go above by default, everything else below.
+    pub fn side(&self) -> Side {
+        if self.modifiers.contains('^') {
+            Side::Above
+        } else if self.modifiers.contains('_') {
+            Side::Below
+        } else if self.kind == AnnotationKind::Label {
+            Side::Above
+        } else {
+            Side::Below
+        }
+    }
+
+    /// If the arrow, or the polarity of the voltage, goes against the bipole.
+    pub fn reversed(&self) -> bool {
+        self.modifiers.contains('<')
+    }
+
+    /// If the current arrow is on the lead before the bipole. Circuitikz puts it there when the
+    /// direction is written before the side, like `i>_`.
+    pub fn at_start(&self) -> bool {
+        let direction = self.modifiers.find(['<', '>']);
+        let side = self.modifiers.find(['_', '^']);
+        matches!((direction, side), (Some(direction), Some(side)) if direction < side)
+    }
+}
+
+impl std::fmt::Display for Annotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.key(), self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_options() {
+        let voltage = Annotation::parse(" v_>= V_1 ").unwrap();
+        assert_eq!(voltage.kind, AnnotationKind::Voltage);
+        assert_eq!(voltage.modifiers, "_>");
+        assert_eq!(voltage.text, "V_1");
+        assert_eq!(voltage.key(), "v_>");
+
+        let flow = Annotation::parse("f<^=q").unwrap();
+        assert_eq!((flow.kind, flow.modifiers.as_str()), (AnnotationKind::Flow, "<^"));
+        assert_eq!(Annotation::parse("a=note").unwrap().kind, AnnotationKind::Annotation);
+        // The plain label, other keys and other modifiers are not annotations.
+        for option in ["l=R_1", "color=red", "v*=V", "i", "x_=1"] {
+            assert_eq!(Annotation::parse(option), None, "{option}");
+        }
+    }
+
+    #[test]
+    fn parses_lists() {
+        let list = Annotation::parse_list("R, l^=$R_{1,2}$, v<={a, {b, c}}, color=red, i>_=I");
+        let keys: Vec<_> = list.iter().map(|annotation| annotation.key()).collect();
+        assert_eq!(keys, ["l^", "v<", "i>_"]);
+        assert_eq!(list[0].text, "$R_{1,2}$");
+        assert_eq!(list[1].text, "{a, {b, c}}");
+        assert!(Annotation::parse_list("").is_empty());
+    }
+
+    #[test]
+    fn placement() {
+        let annotation = |option| Annotation::parse(option).unwrap();
+        assert_eq!(annotation("l^=R").side(), Side::Above);
+        assert_eq!(annotation("l_=R").side(), Side::Below);
+        assert_eq!(annotation("v>=V").side(), Side::Below);
+        assert_eq!(annotation("i^>=I").side(), Side::Above);
+
+        assert!(annotation("i>_=I").at_start());
+        assert!(!annotation("i_>=I").at_start());
+        assert!(!annotation("i>=I").at_start());
+        assert!(annotation("v<=V").reversed());
+        assert!(!annotation("v>=V").reversed());
+    }
+
+    #[test]
+    fn formats_and_reads_back() {
+        for option in ["l^=R_1", "v_>=V_1", "i<^=I_2", "f>=q", "a=note", "l_={a, b}"] {
+            let annotation = Annotation::parse(option).unwrap();
+            assert_eq!(annotation.to_string(), option);
+            assert_eq!(Annotation::parse_list(&annotation.to_string()), [annotation]);
+        }
+    }
+}
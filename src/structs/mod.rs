@@ -1,26 +1,90 @@
 use crate::*;
 
+#[cfg(feature = "gui")]
 use bevy::ecs::{component::ComponentId, world::DeferredWorld};
+use serde::{Deserialize, Serialize};
 
 mod anchor;
+mod annotation;
+#[cfg(feature = "gui")]
 mod cursor_position;
 mod position;
 mod tikz_component;
+mod value;
 
 pub use anchor::*;
+pub use annotation::*;
+#[cfg(feature = "gui")]
 pub use cursor_position::*;
 pub use position::*;
 pub use tikz_component::*;
+pub use value::*;
 
-#[derive(Component, Clone, Copy, Default)]
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub struct BuildInfo {
     pub angle: f32,
     pub len: f32,
+    // Mirroring of node components, applied before the rotation.
+    #[serde(default)]
+    pub flip_x: bool,
+    #[serde(default)]
+    pub flip_y: bool,
 }
 
 impl BuildInfo {
     pub fn new(angle: f32, len: f32) -> Self {
-        Self { angle, len }
+        Self {
+            angle,
+            len,
+            ..default()
+        }
+    }
+
+    pub fn mirror_scale(&self) -> Vec2 {
+        let sign = |flip| if flip { -1. } else { 1. };
+        Vec2::new(sign(self.flip_x), sign(self.flip_y))
+    }
+
+    /// Mirrors and then rotates a point given relative to the center of a node.
+    pub fn orient(&self, offset: Vec2) -> Vec2 {
+        Vec2::from_angle(self.angle).rotate(offset * self.mirror_scale())
+    }
+
+    /// Turns the node by 90 degrees counterclockwise.
+    pub fn rotated(&self) -> Self {
+        Self {
+            angle: (self.angle + std::f32::consts::FRAC_PI_2).rem_euclid(std::f32::consts::TAU),
+            ..*self
+        }
+    }
+
+    /// Mirrors the node as seen on the canvas, so a rotated node flips on the screen axis.
+    pub fn mirrored(&self, vertical: bool) -> Self {
+        // A quarter turn swaps which local axis lies on the horizontal of the screen.
+        let quarter_turns = (self.angle / std::f32::consts::FRAC_PI_2).round() as i32;
+        let mut result = *self;
+        if vertical == (quarter_turns % 2 == 0) {
+            result.flip_y = !self.flip_y;
+        } else {
+            result.flip_x = !self.flip_x;
+        }
+        result
+    }
+
+    /// Options that orient a node in circuitikz, in the same order they are applied here.
+    pub fn node_options(&self) -> String {
+        let mut buf = String::default();
+        let degrees = self.angle.to_degrees().round() as i32 % 360;
+        if degrees != 0 {
+            buf.push_str(&format!(", rotate={degrees}"));
+        }
+        if self.flip_x {
+            buf.push_str(", xscale=-1");
+        }
+        if self.flip_y {
+            buf.push_str(", yscale=-1");
+        }
+        buf
     }
 }
 
@@ -31,21 +95,37 @@ impl std::fmt::Display for BuildInfo {
 }
 
 // NOTE:This label is what appear in the circuit
-#[derive(Component, Clone)]
-#[component(on_insert = on_insert_hook)]
+#[derive(Component, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "gui", component(on_insert = on_insert_hook))]
 pub struct Info {
     pub label: String,
     pub scale: String,
+    /// Voltage, current, flow and placed labels of a bipole.
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    /// Resistance, capacitance, inductance or amplitude, in base SI units.
+    #[serde(default)]
+    pub value: Option<f64>,
 }
 
 // NOTE: This considers that the "label" or "text" entity is the first child.
+#[cfg(feature = "gui")]
 fn on_insert_hook(mut world: DeferredWorld, entity: Entity, _component: ComponentId) {
     let Some(children) = world.entity(entity).get::<Children>() else {
         return;
     };
 
     let text_ent = *children.first().unwrap();
-    let new_text = Text2d::new(world.get::<Info>(entity).unwrap().label.clone());
+    let info = world.get::<Info>(entity).unwrap();
+    let value = info
+        .value
+        .zip(world.get::<TikzComponent>(entity).and_then(|cc| cc.quantity()))
+        .map(|(value, quantity)| format_value(value, quantity));
+    let new_text = Text2d::new(match value {
+        Some(value) if info.label.is_empty() => value,
+        Some(value) => format!("{}\n{value}", info.label),
+        None => info.label.clone(),
+    });
     if let Some(mut text) = world.get_mut::<Text2d>(text_ent) {
         *text = new_text;
     }
@@ -54,17 +134,63 @@ fn on_insert_hook(mut world: DeferredWorld, entity: Entity, _component: Componen
 impl Info {
     pub fn with_scale(&mut self, scale: String) -> Self {
         Self {
-            label: self.label.clone(),
             scale,
+            ..self.clone()
         }
     }
 
     pub fn with_label(&mut self, label: String) -> Self {
         Self {
             label,
-            scale: self.scale.clone(),
+            ..self.clone()
+        }
+    }
+
+    pub fn with_value(&mut self, value: Option<f64>) -> Self {
+        Self {
+            value,
+            ..self.clone()
+        }
+    }
+
+    pub fn with_annotations(&mut self, annotations: Vec<Annotation>) -> Self {
+        Self {
+            annotations,
+            ..self.clone()
+        }
+    }
+
+    /// Annotations written as the options they are exported as.
+    pub fn annotations_text(&self) -> String {
+        self.annotations
+            .iter()
+            .map(|annotation| annotation.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+    /// Type of the component followed by its options, with the value written in `style`. When the
+    /// component also has a label, the value goes to the annotation on the other side, unless an
+    /// annotation is already there.
+    pub fn tikz_options(&self, cc: TikzComponent, style: ValueStyle) -> String {
+        let value = self
+            .value
+            .zip(cc.quantity())
+            .map(|(value, quantity)| export_value(value, quantity, style));
+        let annotated = self
+            .annotations
+            .iter()
+            .any(|annotation| annotation.kind == AnnotationKind::Annotation);
+        match value {
+            Some(value) if self.label.is_empty() => {
+                format!("{}={value}{}", cc.tikz_type(), self.get_component_info())
+            }
+            Some(value) if !annotated => {
+                format!("{}{}, a={value}", cc.tikz_type(), self.get_component_info())
+            }
+            _ => format!("{}{}", cc.tikz_type(), self.get_component_info()),
         }
     }
+
     pub fn get_component_info(&self) -> String {
         let mut buf = String::default();
         if !self.label.is_empty() {
@@ -75,6 +201,10 @@ impl Info {
             buf.push_str(&format!(", scale={}", self.scale));
         }
 
+        for annotation in &self.annotations {
+            buf.push_str(&format!(", {annotation}"));
+        }
+
         buf
     }
 }
@@ -84,6 +214,39 @@ impl Default for Info {
         Self {
             label: Default::default(),
             scale: 1.0.to_string(),
+            annotations: Vec::new(),
+            value: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_options() {
+        let build_info = BuildInfo::default();
+        assert_eq!(build_info.node_options(), "");
+        assert_eq!(build_info.rotated().node_options(), ", rotate=90");
+        assert_eq!(build_info.mirrored(false).node_options(), ", xscale=-1");
+        assert_eq!(build_info.mirrored(true).node_options(), ", yscale=-1");
+        let turned = build_info.rotated().rotated().rotated();
+        assert_eq!(turned.node_options(), ", rotate=270");
+        assert_eq!(turned.rotated().node_options(), "");
+    }
+
+    #[test]
+    fn mirrors_on_the_screen_axis() {
+        // Turned a quarter, the local y axis lies on the horizontal of the screen.
+        let turned = BuildInfo::default().rotated();
+        assert_eq!(turned.mirrored(false).node_options(), ", rotate=90, yscale=-1");
+        assert_eq!(turned.mirrored(true).node_options(), ", rotate=90, xscale=-1");
+        assert_eq!(turned.mirrored(false).mirrored(false), turned);
+
+        let flipped = BuildInfo::default().mirrored(false).rotated();
+        assert!(flipped.orient(Vec2::X).abs_diff_eq(Vec2::NEG_Y, 1e-6));
+        let offset = turned.mirrored(false).orient(Vec2::new(2., 1.));
+        assert!(offset.abs_diff_eq(Vec2::new(1., 2.), 1e-6), "{offset}");
+    }
+}
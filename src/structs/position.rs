@@ -1,5 +1,8 @@
 use bevy::prelude::*;
 
+/// Point of the canvas at `(0, 0)` in the exported TikZ.
+pub const TIKZ_ORIGIN: Vec2 = Vec2::new(160., 0.);
+
 #[derive(Debug, Default, Eq, PartialOrd, Ord, Hash, Component, Clone, Copy, PartialEq)]
 pub struct Position {
     pub x: isize,
@@ -13,6 +16,14 @@ impl Position {
             y: self.y as f32 * 0.03125,
         }
     }
+
+    pub fn from_tikz_coords(tikz: Vec2) -> Self {
+        Vec2 {
+            x: (tikz.x + 5.) * 32.,
+            y: tikz.y * 32.,
+        }
+        .into()
+    }
 }
 
 impl From<Vec2> for Position {
@@ -23,3 +34,12 @@ impl From<Vec2> for Position {
         }
     }
 }
+
+impl From<Position> for Vec2 {
+    fn from(pos: Position) -> Self {
+        Vec2 {
+            x: pos.x as f32,
+            y: pos.y as f32,
+        }
+    }
+}
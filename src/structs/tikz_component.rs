@@ -1,22 +1,61 @@
+use super::BuildInfo;
+#[cfg(feature = "gui")]
 use crate::actions::UpdateComponentLabel;
-use bevy::{
-    ecs::{component::ComponentId, world::DeferredWorld},
-    prelude::*,
-};
+use crate::symbols::{SymbolId, SymbolKind};
+use crate::GRID_SIZE;
+use bevy::prelude::*;
+#[cfg(feature = "gui")]
+use bevy::ecs::{component::ComponentId, world::DeferredWorld};
+use serde::{Deserialize, Serialize};
 
 // This label is to call the "coordinate" of that component.
 #[derive(Component)]
-#[component(on_add = hook)]
-#[component(on_remove = hook)]
+#[cfg_attr(feature = "gui", component(on_add = hook))]
+#[cfg_attr(feature = "gui", component(on_remove = hook))]
 pub struct ComponentLabel {
     pub label: String,
 }
 
+#[cfg(feature = "gui")]
 fn hook(mut world: DeferredWorld, _: Entity, _component_id: ComponentId) {
     world.trigger::<UpdateComponentLabel>(UpdateComponentLabel);
 }
 
 impl ComponentLabel {
+    /// Labels of the components, in the order they are given. Each keeps its own label unless
+    /// an earlier one has it, and the others are numbered by type, `AND1`, `AND2`, `E1`...,
+    /// skipping the labels that are taken.
+    pub fn numbered<'a>(components: impl IntoIterator<Item = (TikzComponent, &'a str)>) -> Vec<Self> {
+        let components: Vec<_> = components.into_iter().collect();
+        let mut taken = bevy::utils::HashSet::new();
+        let kept: Vec<bool> = components
+            .iter()
+            .map(|(_, label)| !label.is_empty() && taken.insert(label.to_string()))
+            .collect();
+
+        let mut map = bevy::utils::HashMap::<&str, u32>::new();
+        components
+            .into_iter()
+            .zip(kept)
+            .map(|((component, label), kept)| {
+                let f_type = component.label_prefix();
+                let count = map.entry(f_type).or_insert(0);
+                *count += 1;
+                if kept {
+                    return Self {
+                        label: label.to_string(),
+                    };
+                }
+                while taken.contains(&format!("{f_type}{count}")) {
+                    *count += 1;
+                }
+                let label = format!("{f_type}{count}");
+                taken.insert(label.clone());
+                Self { label }
+            })
+            .collect()
+    }
+
     pub fn get_label(&self) -> String {
         format!("({})", self.label)
     }
@@ -28,13 +67,28 @@ impl std::fmt::Display for ComponentLabel {
     }
 }
 
-#[derive(Component, Debug, Copy, Clone)]
+#[derive(Component, Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ComponentStructure {
     Node(Vec2),
     To([Vec2; 2]),
 }
 
-#[derive(Debug, PartialEq, Hash, PartialOrd, Ord, Eq, Component, Copy, Clone, Resource)]
+impl ComponentStructure {
+    /// Position of each pin of a node, following its orientation.
+    pub fn pins(&self, kind: TikzComponent, build_info: BuildInfo) -> Vec<(&'static str, Vec2)> {
+        let Self::Node(pos) = *self else {
+            return Vec::new();
+        };
+        kind.pins()
+            .iter()
+            .map(|&(label, offset)| (label, pos + build_info.orient(offset * GRID_SIZE)))
+            .collect()
+    }
+}
+
+#[derive(
+    Debug, PartialEq, Hash, PartialOrd, Ord, Eq, Component, Copy, Clone, Resource, Serialize, Deserialize,
+)]
 pub enum TikzComponent {
     AndGate,
     OrGate,
@@ -52,22 +106,113 @@ pub enum TikzComponent {
     Transistor,
     Diode,
     Transformer,
+    NandGate,
+    NorGate,
+    XnorGate,
+    Pnp,
+    Nmos,
+    Pmos,
+    Zener,
+    Led,
+    Battery,
+    SinVSource,
+    SinISource,
+    ControlledVSource,
+    ControlledISource,
+    Switch,
+    ClosedSwitch,
+    Lamp,
+    Fuse,
+    Ammeter,
+    Voltmeter,
+    VariableResistor,
+    /// Symbol of the library file.
+    Custom(SymbolId),
 }
 
+const GATE_PINS: [(&str, Vec2); 3] = [
+    (".in 1", Vec2::new(-2., 1.)),
+    (".in 2", Vec2::new(-2., -1.)),
+    (".out", Vec2::new(2., 0.)),
+];
+const NOT_PINS: [(&str, Vec2); 2] = [(".in", Vec2::new(-2., 0.)), (".out", Vec2::new(2., 0.))];
+const NPN_PINS: [(&str, Vec2); 3] = [
+    (".B", Vec2::new(-2., 0.)),
+    (".C", Vec2::new(0., 2.)),
+    (".E", Vec2::new(0., -2.)),
+];
+const PNP_PINS: [(&str, Vec2); 3] = [
+    (".B", Vec2::new(-2., 0.)),
+    (".C", Vec2::new(0., -2.)),
+    (".E", Vec2::new(0., 2.)),
+];
+const NMOS_PINS: [(&str, Vec2); 3] = [
+    (".G", Vec2::new(-2., 0.)),
+    (".D", Vec2::new(0., 2.)),
+    (".S", Vec2::new(0., -2.)),
+];
+const PMOS_PINS: [(&str, Vec2); 3] = [
+    (".G", Vec2::new(-2., 0.)),
+    (".D", Vec2::new(0., -2.)),
+    (".S", Vec2::new(0., 2.)),
+];
+// Primary on the left, secondary on the right.
+const TRANSFORMER_PINS: [(&str, Vec2); 4] = [
+    (".A1", Vec2::new(-2., 2.)),
+    (".A2", Vec2::new(-2., -2.)),
+    (".B1", Vec2::new(2., 2.)),
+    (".B2", Vec2::new(2., -2.)),
+];
+const AMP_PINS: [(&str, Vec2); 3] = [
+    (".-", Vec2::new(-2., 1.)),
+    (".+", Vec2::new(-2., -1.)),
+    (".out", Vec2::new(3., 0.)),
+];
+
 impl TikzComponent {
+    pub const ALL: [TikzComponent; 36] = {
+        use TikzComponent::*;
+        [
+            AndGate, OrGate, XorGate, NotGate, Dot, Resistor, Capacitor, Inductor, Ground, VSource,
+            ISource, Line, AmpOp, Transistor, Diode, Transformer, NandGate, NorGate, XnorGate, Pnp,
+            Nmos, Pmos, Zener, Led, Battery, SinVSource, SinISource, ControlledVSource,
+            ControlledISource, Switch, ClosedSwitch, Lamp, Fuse, Ammeter, Voltmeter,
+            VariableResistor,
+        ]
+    };
+
+    /// Built in components followed by the symbols of the library.
+    pub fn all() -> impl Iterator<Item = TikzComponent> {
+        Self::ALL
+            .into_iter()
+            .chain(SymbolId::all().map(TikzComponent::Custom))
+    }
+
     #[inline]
     pub fn is_single(&self) -> bool {
         use TikzComponent::*;
-        matches!(self, Ground | Dot | AmpOp | Transistor | Transformer) || self.is_gate()
+        if let Custom(id) = self {
+            return id.symbol().kind == SymbolKind::Node;
+        }
+        matches!(self, Ground | Dot | AmpOp | Transformer) || self.is_gate() || self.is_transistor()
     }
 
     #[inline]
     pub fn is_gate(&self) -> bool {
         use TikzComponent::*;
-        matches!(self, AndGate | OrGate | XorGate | NotGate)
+        matches!(
+            self,
+            AndGate | OrGate | XorGate | NotGate | NandGate | NorGate | XnorGate
+        )
     }
 
-    pub fn tikz_type(&self) -> &str {
+    #[inline]
+    pub fn is_transistor(&self) -> bool {
+        use TikzComponent::*;
+        matches!(self, Transistor | Pnp | Nmos | Pmos)
+    }
+
+    pub fn tikz_type(&self) -> &'static str {
         use TikzComponent::*;
         match self {
             Resistor => "R",
@@ -86,16 +231,80 @@ impl TikzComponent {
             Transistor => "npn",
             Diode => "D",
             Transformer => "transformer",
+            NandGate => "nand port",
+            NorGate => "nor port",
+            XnorGate => "xnor port",
+            Pnp => "pnp",
+            Nmos => "nmos",
+            Pmos => "pmos",
+            Zener => "zD",
+            Led => "leD",
+            Battery => "battery1",
+            SinVSource => "sV",
+            SinISource => "sI",
+            ControlledVSource => "cV",
+            ControlledISource => "cI",
+            Switch => "nos",
+            ClosedSwitch => "ncs",
+            Lamp => "lamp",
+            Fuse => "fuse",
+            Ammeter => "ammeter",
+            Voltmeter => "voltmeter",
+            VariableResistor => "vR",
+            Custom(id) => &id.symbol().tikz,
             // Label => panic!("Reaching tikz_type with type label"),
         }
     }
 
+    pub fn from_tikz_type(tikz_type: &str) -> Option<Self> {
+        Self::all().find(|component| component.tikz_type() == tikz_type)
+    }
+
+    /// Anchors of the pins of the component, relative to its position and in grid units. Bipoles
+    /// have none, they connect at their ends: the diode goes from the anode to the cathode.
+    pub fn pins(&self) -> &'static [(&'static str, Vec2)] {
+        use TikzComponent::*;
+        match self {
+            AndGate | OrGate | XorGate | NandGate | NorGate | XnorGate => &GATE_PINS,
+            NotGate => &NOT_PINS,
+            AmpOp => &AMP_PINS,
+            Transistor => &NPN_PINS,
+            Pnp => &PNP_PINS,
+            Nmos => &NMOS_PINS,
+            Pmos => &PMOS_PINS,
+            Transformer => &TRANSFORMER_PINS,
+            Custom(id) => &id.symbol().pins,
+            _ => &[],
+        }
+    }
+
+    /// Prefix of the label the component is called by in the exported circuit.
+    pub fn label_prefix(&self) -> &'static str {
+        use TikzComponent::*;
+        match self {
+            AndGate => "AND",
+            OrGate => "OR",
+            XorGate => "XOR",
+            NotGate => "NOT",
+            NandGate => "NAND",
+            NorGate => "NOR",
+            XnorGate => "XNOR",
+            AmpOp => "AOP",
+            Transformer => "T",
+            x if x.is_transistor() => "S",
+            Custom(id) => &id.symbol().label_prefix,
+            _ => "E",
+        }
+    }
+
     pub fn get_label_height(&self) -> f32 {
         use TikzComponent::*;
         match self {
             AmpOp => 0.,
             x if x.is_gate() => 2.,
+            Transformer => 2.5,
             Line => 0.75,
+            Custom(id) => id.symbol().label_height,
             _ => 1.5,
         }
     }
@@ -121,6 +330,27 @@ impl std::fmt::Display for TikzComponent {
             Transistor => "Transistor",
             Diode => "Diodo",
             Transformer => "Trafo",
+            NandGate => "Nand",
+            NorGate => "Nor",
+            XnorGate => "Xnor",
+            Pnp => "PNP",
+            Nmos => "NMOS",
+            Pmos => "PMOS",
+            Zener => "Zener",
+            Led => "LED",
+            Battery => "Bateria",
+            SinVSource => "V~",
+            SinISource => "I~",
+            ControlledVSource => "V dep",
+            ControlledISource => "I dep",
+            Switch => "Chave",
+            ClosedSwitch => "Chave F",
+            Lamp => "Lâmpada",
+            Fuse => "Fusível",
+            Ammeter => "Amperím.",
+            Voltmeter => "Voltím.",
+            VariableResistor => "R var",
+            Custom(id) => &id.symbol().name,
         };
         write!(f, "{c}")
     }
@@ -0,0 +1,462 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::TikzComponent;
+
+/// How component values are written in the exported circuit.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValueStyle {
+    /// `R=4.7<\kilo\ohm>`, typeset by siunitx through the circuitikz `siunitx` option.
+    #[default]
+    Siunitx,
+    /// `R={$4.7\,\mathrm{k}\Omega$}`, for documents without siunitx.
+    Plain,
+}
+
+/// What the value of a component measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    Resistance,
+    Capacitance,
+    Inductance,
+    Voltage,
+    Current,
+    Frequency,
+    /// Ratio of a controlled source to what controls it, without a unit or a prefix.
+    Gain,
+}
+
+impl Quantity {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Resistance => "Ω",
+            Self::Capacitance => "F",
+            Self::Inductance => "H",
+            Self::Voltage => "V",
+            Self::Current => "A",
+            Self::Frequency => "Hz",
+            Self::Gain => "",
+        }
+    }
+
+    fn siunitx(&self) -> &'static str {
+        match self {
+            Self::Resistance => "\\ohm",
+            Self::Capacitance => "\\farad",
+            Self::Inductance => "\\henry",
+            Self::Voltage => "\\volt",
+            Self::Current => "\\ampere",
+            Self::Frequency => "\\hertz",
+            Self::Gain => "",
+        }
+    }
+
+    fn latex(&self) -> &'static str {
+        match self {
+            Self::Resistance => "\\Omega",
+            Self::Capacitance => "\\mathrm{F}",
+            Self::Inductance => "\\mathrm{H}",
+            Self::Voltage => "\\mathrm{V}",
+            Self::Current => "\\mathrm{A}",
+            Self::Frequency => "\\mathrm{Hz}",
+            Self::Gain => "",
+        }
+    }
+}
+
+impl TikzComponent {
+    /// Quantity of the value the component takes, if it takes one. Sources take their amplitude.
+    pub fn quantity(&self) -> Option<Quantity> {
+        use TikzComponent::*;
+        Some(match self {
+            Resistor | VariableResistor => Quantity::Resistance,
+            Capacitor => Quantity::Capacitance,
+            Inductor => Quantity::Inductance,
+            VSource | SinVSource | Battery => Quantity::Voltage,
+            ISource | SinISource => Quantity::Current,
+            ControlledVSource | ControlledISource => Quantity::Gain,
+            _ => return None,
+        })
+    }
+}
+
+/// SI prefix with its symbol, siunitx macro and LaTeX form.
+struct Prefix {
+    exponent: i32,
+    symbol: &'static str,
+    siunitx: &'static str,
+    latex: &'static str,
+}
+
+const PREFIXES: [Prefix; 9] = [
+    Prefix {
+        exponent: -15,
+        symbol: "f",
+        siunitx: "\\femto",
+        latex: "\\mathrm{f}",
+    },
+    Prefix {
+        exponent: -12,
+        symbol: "p",
+        siunitx: "\\pico",
+        latex: "\\mathrm{p}",
+    },
+    Prefix {
+        exponent: -9,
+        symbol: "n",
+        siunitx: "\\nano",
+        latex: "\\mathrm{n}",
+    },
+    Prefix {
+        exponent: -6,
+        symbol: "µ",
+        siunitx: "\\micro",
+        latex: "\\mu",
+    },
+    Prefix {
+        exponent: -3,
+        symbol: "m",
+        siunitx: "\\milli",
+        latex: "\\mathrm{m}",
+    },
+    Prefix {
+        exponent: 0,
+        symbol: "",
+        siunitx: "",
+        latex: "",
+    },
+    Prefix {
+        exponent: 3,
+        symbol: "k",
+        siunitx: "\\kilo",
+        latex: "\\mathrm{k}",
+    },
+    Prefix {
+        exponent: 6,
+        symbol: "M",
+        siunitx: "\\mega",
+        latex: "\\mathrm{M}",
+    },
+    Prefix {
+        exponent: 9,
+        symbol: "G",
+        siunitx: "\\giga",
+        latex: "\\mathrm{G}",
+    },
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueError {
+    Empty,
+    Invalid(String),
+}
+
+impl std::fmt::Display for ValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Empty value"),
+            Self::Invalid(text) => write!(f, "Invalid value {text}"),
+        }
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+fn multiplier(prefix: &str) -> Option<f64> {
+    let exponent = match prefix {
+        "f" => -15,
+        "p" => -12,
+        "n" => -9,
+        "u" | "µ" | "μ" => -6,
+        "m" => -3,
+        // The decimal point of resistances written like `2R2`.
+        "" | "r" | "R" => 0,
+        "k" | "K" => 3,
+        "M" | "meg" | "MEG" => 6,
+        "G" => 9,
+        "T" => 12,
+        _ => return None,
+    };
+    Some(10f64.powi(exponent))
+}
+
+/// Parses values in engineering notation, like `4k7`, `10u`, `2.2 MΩ`, `100nF`, `-5m` or `1e3`.
+/// The unit of `quantity` may follow the number. Infinite values and NaN are refused.
+pub fn parse_value(text: &str, quantity: Quantity) -> Result<f64, ValueError> {
+    let invalid = || ValueError::Invalid(text.to_string());
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.is_empty() {
+        return Err(ValueError::Empty);
+    }
+
+    let mut number = compact.as_str();
+    let units: &[&str] = match quantity {
+        Quantity::Resistance => &["ohms", "ohm", "Ω", "Ω"],
+        Quantity::Capacitance => &["F"],
+        Quantity::Inductance => &["H"],
+        Quantity::Voltage => &["V"],
+        Quantity::Current => &["A"],
+        Quantity::Frequency => &["Hz"],
+        Quantity::Gain => &[],
+    };
+    for unit in units {
+        if let Some(rest) = number.strip_suffix(unit).filter(|rest| !rest.is_empty()) {
+            number = rest;
+            break;
+        }
+    }
+
+    // The sign is taken off before the prefix is looked for, so `-4k7` reads like `4k7`.
+    let (sign, number) = match number.strip_prefix('-') {
+        Some(rest) => (-1., rest),
+        None => (1., number.strip_prefix('+').unwrap_or(number)),
+    };
+    if number.starts_with(['-', '+']) {
+        return Err(invalid());
+    }
+
+    let value = match number.parse::<f64>() {
+        Ok(value) => value,
+        Err(_) => parse_prefixed(number).ok_or_else(invalid)?,
+    };
+    Some(sign * value)
+        .filter(|value| value.is_finite())
+        .ok_or_else(invalid)
+}
+
+/// Digits, a prefix and maybe more digits, which come after the decimal point: `4k7`.
+fn parse_prefixed(number: &str) -> Option<f64> {
+    let start = number.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+    let (whole, rest) = number.split_at(start);
+    let end = rest
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let (prefix, fraction) = rest.split_at(end);
+    if whole.is_empty() || (!fraction.is_empty() && whole.contains('.')) {
+        return None;
+    }
+
+    let multiplier = multiplier(prefix)?;
+    let mantissa: f64 = format!("{whole}.{fraction}")
+        .trim_end_matches('.')
+        .parse()
+        .ok()?;
+    Some(mantissa * multiplier)
+}
+
+/// Splits a value into its mantissa, between 1 and 1000, and its prefix. Values past the range
+/// of the prefixes keep the first or the last one.
+fn engineering(value: f64) -> (f64, &'static Prefix) {
+    let (first, last) = (PREFIXES[0].exponent, PREFIXES[PREFIXES.len() - 1].exponent);
+    let exponent = if value == 0. || !value.is_finite() {
+        0
+    } else {
+        // Clamped before the cast, so huge values do not overflow it.
+        let steps = (value.abs().log10() / 3.).floor();
+        steps.clamp(f64::from(first / 3), f64::from(last / 3)) as i32 * 3
+    };
+    let prefix = PREFIXES
+        .iter()
+        .find(|prefix| prefix.exponent == exponent)
+        .unwrap_or(if exponent < 0 {
+            &PREFIXES[0]
+        } else {
+            &PREFIXES[PREFIXES.len() - 1]
+        });
+    (value / 10f64.powi(prefix.exponent), prefix)
+}
+
+/// Mantissa without trailing zeros, rounded to what a component value needs.
+fn mantissa(value: f64) -> String {
+    let text = format!("{value:.3}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Value as shown on the canvas, like `4.7 kΩ`. Gains are written as they are.
+pub fn format_value(value: f64, quantity: Quantity) -> String {
+    if quantity == Quantity::Gain {
+        return value.to_string();
+    }
+    let (value, prefix) = engineering(value);
+    format!("{} {}{}", mantissa(value), prefix.symbol, quantity.symbol())
+}
+
+/// Value as written in the exported circuit, after the `=` of the component type.
+pub fn export_value(value: f64, quantity: Quantity, style: ValueStyle) -> String {
+    if quantity == Quantity::Gain {
+        return value.to_string();
+    }
+    let (value, prefix) = engineering(value);
+    let value = mantissa(value);
+    match style {
+        ValueStyle::Siunitx => format!("{value}<{}{}>", prefix.siunitx, quantity.siunitx()),
+        // Braced, since the thin space has a comma that would end the option.
+        ValueStyle::Plain => format!("{{${value}\\,{}{}$}}", prefix.latex, quantity.latex()),
+    }
+}
+
+/// Value as written in a SPICE card, like `4.7k` or `1meg`. SPICE reads `M` as milli.
+pub fn spice_value(value: f64) -> String {
+    let (_, prefix) = engineering(value);
+    let suffix = match prefix.symbol {
+        "µ" => "u",
+        "M" => "meg",
+        symbol => symbol,
+    };
+    format!("{}{suffix}", shifted(value, prefix.exponent))
+}
+
+/// `value` divided by ten to the `exponent`, keeping every digit of it. The digits are moved
+/// instead of dividing, which would round the last one.
+fn shifted(value: f64, exponent: i32) -> String {
+    if !value.is_finite() {
+        return value.to_string();
+    }
+    // The shortest digits that read back as the value, like `-4.7012e3`.
+    let text = format!("{value:e}");
+    let (mantissa, power) = text.split_once('e').unwrap();
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace('.', "");
+    // Digits before the point.
+    let whole = power.parse::<i32>().unwrap() - exponent + 1;
+    let number = if whole <= 0 {
+        format!("0.{}{digits}", "0".repeat(whole.unsigned_abs() as usize))
+    } else if whole as usize >= digits.len() {
+        format!("{digits}{}", "0".repeat(whole as usize - digits.len()))
+    } else {
+        let (whole, fraction) = digits.split_at(whole as usize);
+        format!("{whole}.{fraction}")
+    };
+    format!("{sign}{number}")
+}
+
+/// Reads back a value written by [`export_value`] in either style.
+pub fn parse_exported_value(text: &str, quantity: Quantity) -> Option<f64> {
+    let text = text.trim();
+    if quantity == Quantity::Gain {
+        return text.parse().ok().filter(|value: &f64| value.is_finite());
+    }
+    if let Some((number, unit)) = text.strip_suffix('>').and_then(|text| text.split_once('<')) {
+        let number: f64 = number.trim().parse().ok()?;
+        let prefix = PREFIXES
+            .iter()
+            .filter(|prefix| !prefix.siunitx.is_empty())
+            .find(|prefix| unit.trim().starts_with(prefix.siunitx));
+        return Some(number * 10f64.powi(prefix.map_or(0, |prefix| prefix.exponent)));
+    }
+
+    let text = text
+        .strip_prefix('{')
+        .and_then(|text| text.strip_suffix('}'))
+        .unwrap_or(text);
+    let inner = text.strip_prefix('$')?.strip_suffix('$')?;
+    let (number, unit) = inner.split_once("\\,")?;
+    let number: f64 = number.trim().parse().ok()?;
+    let prefix = PREFIXES
+        .iter()
+        .filter(|prefix| !prefix.latex.is_empty())
+        .find(|prefix| unit.starts_with(prefix.latex) && unit != quantity.latex());
+    Some(number * 10f64.powi(prefix.map_or(0, |prefix| prefix.exponent)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(value: f64, expected: f64) {
+        assert!(
+            (value - expected).abs() <= 1e-9 * expected.abs().max(1.),
+            "{value} is not {expected}"
+        );
+    }
+
+    #[test]
+    fn parses_engineering_notation() {
+        for (text, quantity, expected) in [
+            ("4k7", Quantity::Resistance, 4700.),
+            ("2R2", Quantity::Resistance, 2.2),
+            ("2.2 MΩ", Quantity::Resistance, 2.2e6),
+            ("10 ohms", Quantity::Resistance, 10.),
+            ("100nF", Quantity::Capacitance, 100e-9),
+            ("10u", Quantity::Inductance, 10e-6),
+            ("1meg", Quantity::Frequency, 1e6),
+            ("1e3", Quantity::Frequency, 1e3),
+        ] {
+            assert_close(parse_value(text, quantity).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn parses_signed_values() {
+        for (text, expected) in [("-5m", -5e-3), ("-4k7", -4700.), ("-12 V", -12.), ("+3.3V", 3.3)] {
+            let quantity = Quantity::Voltage;
+            assert_close(parse_value(text, quantity).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn refuses_invalid_values() {
+        assert_eq!(parse_value(" ", Quantity::Resistance), Err(ValueError::Empty));
+        for text in ["inf", "-inf", "NaN", "1e400", "--5", "k", "4k7k", "1.2k3", "5x"] {
+            assert_eq!(
+                parse_value(text, Quantity::Resistance),
+                Err(ValueError::Invalid(text.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn formats_with_prefixes() {
+        assert_eq!(format_value(4700., Quantity::Resistance), "4.7 kΩ");
+        assert_eq!(format_value(-0.005, Quantity::Voltage), "-5 mV");
+        assert_eq!(format_value(0., Quantity::Current), "0 A");
+        // Past the prefixes, the last one is kept.
+        assert_eq!(format_value(2e12, Quantity::Frequency), "2000 GHz");
+        assert_eq!(format_value(f64::INFINITY, Quantity::Voltage), "inf V");
+        assert!(format_value(f64::MAX, Quantity::Voltage).ends_with(" GV"));
+    }
+
+    #[test]
+    fn exports_and_reads_back() {
+        let siunitx = export_value(4700., Quantity::Resistance, ValueStyle::Siunitx);
+        assert_eq!(siunitx, "4.7<\\kilo\\ohm>");
+        let plain = export_value(4700., Quantity::Resistance, ValueStyle::Plain);
+        assert_eq!(plain, "{$4.7\\,\\mathrm{k}\\Omega$}");
+        for text in [siunitx, plain] {
+            assert_close(parse_exported_value(&text, Quantity::Resistance).unwrap(), 4700.);
+        }
+        let ohms = export_value(5., Quantity::Resistance, ValueStyle::Plain);
+        assert_close(parse_exported_value(&ohms, Quantity::Resistance).unwrap(), 5.);
+    }
+
+    #[test]
+    fn gains_have_no_unit() {
+        assert_eq!(TikzComponent::ControlledVSource.quantity(), Some(Quantity::Gain));
+        assert_eq!(format_value(0.5, Quantity::Gain), "0.5");
+        assert_close(parse_value("4k7", Quantity::Gain).unwrap(), 4700.);
+        for style in [ValueStyle::Siunitx, ValueStyle::Plain] {
+            let text = export_value(2000., Quantity::Gain, style);
+            assert_eq!(text, "2000");
+            assert_eq!(parse_exported_value(&text, Quantity::Gain), Some(2000.));
+        }
+    }
+
+    #[test]
+    fn spice_values() {
+        assert_eq!(spice_value(4700.), "4.7k");
+        assert_eq!(spice_value(1e6), "1meg");
+        assert_eq!(spice_value(4.7e-6), "4.7u");
+        assert_eq!(spice_value(-5e-3), "-5m");
+        // Written at full precision, unlike the values on the canvas.
+        assert_eq!(spice_value(4701.2), "4.7012k");
+        assert_eq!(spice_value(0.1234567), "123.4567m");
+        assert_eq!(spice_value(0.), "0");
+        assert_eq!(spice_value(12.), "12");
+        // Past the prefixes, the first or the last one is kept.
+        assert_eq!(spice_value(2e-18), "0.002f");
+        assert_eq!(spice_value(2e12), "2000G");
+    }
+}
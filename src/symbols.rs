@@ -0,0 +1,257 @@
+//! Components described in a symbol library file instead of the code.
+//!
+//! The library is a RON file read once at startup. Each symbol has its circuitikz name, whether it
+//! is a node or a bipole, the anchors of its pins and the polylines, arcs and circles drawn on the
+//! canvas. Every coordinate is in grid cells, relative to the position of a node or to the middle
+//! of a bipole, whose body goes from x = -0.75 to 0.75 along its length.
+
+use std::sync::OnceLock;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Library read when no other file is given.
+pub const DEFAULT_LIBRARY: &str = "assets/symbols.ron";
+
+static LIBRARY: OnceLock<SymbolLibrary> = OnceLock::new();
+
+/// Loads the library before anything asks for it, so the custom symbols are in the palette.
+pub struct SymbolsPlugin(pub String);
+
+impl Plugin for SymbolsPlugin {
+    fn build(&self, _app: &mut App) {
+        match load_library(&self.0) {
+            Ok(0) => {}
+            Ok(count) => info!("Loaded {count} symbols from {}", self.0),
+            Err(err) => error!("Could not load the symbol library {}: {err}", self.0),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Node,
+    Bipole,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub enum Shape {
+    /// Points joined one after the other.
+    Polyline(Vec<Vec2>),
+    /// Arc around a center, from `start` to `end` degrees counterclockwise.
+    Arc {
+        center: Vec2,
+        radius: f32,
+        start: f32,
+        end: f32,
+    },
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+}
+
+fn default_label_height() -> f32 {
+    1.5
+}
+
+fn default_label_prefix() -> String {
+    "E".to_string()
+}
+
+/// A symbol as written in the library file.
+#[derive(Deserialize, Debug, Clone)]
+struct SymbolDef {
+    name: String,
+    tikz: String,
+    kind: SymbolKind,
+    #[serde(default = "default_label_prefix")]
+    label_prefix: String,
+    #[serde(default = "default_label_height")]
+    label_height: f32,
+    #[serde(default)]
+    pins: Vec<(String, Vec2)>,
+    geometry: Vec<Shape>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct LibraryFile {
+    symbols: Vec<SymbolDef>,
+}
+
+#[derive(Debug)]
+pub struct Symbol {
+    pub name: String,
+    pub tikz: String,
+    pub kind: SymbolKind,
+    pub label_prefix: String,
+    pub label_height: f32,
+    pub pins: Vec<(&'static str, Vec2)>,
+    pub geometry: Vec<Shape>,
+}
+
+impl From<SymbolDef> for Symbol {
+    fn from(def: SymbolDef) -> Self {
+        // The library lives until the end of the program, so its pin names can be leaked to
+        // give them the same lifetime as the pins of the built in components.
+        let pins = def
+            .pins
+            .into_iter()
+            .map(|(name, offset)| (&*Box::leak(name.into_boxed_str()), offset))
+            .collect();
+        Self {
+            name: def.name,
+            tikz: def.tikz,
+            kind: def.kind,
+            label_prefix: def.label_prefix,
+            label_height: def.label_height,
+            pins,
+            geometry: def.geometry,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SymbolLibrary {
+    pub symbols: Vec<Symbol>,
+}
+
+#[derive(Debug)]
+pub enum SymbolError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    AlreadyLoaded,
+}
+
+impl std::fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse(err) => write!(f, "Invalid symbol library: {err}"),
+            Self::AlreadyLoaded => write!(f, "A symbol library was already loaded"),
+        }
+    }
+}
+
+impl std::error::Error for SymbolError {}
+
+/// Reads the library at `path` and returns how many symbols it has. A missing file is an empty
+/// library.
+pub fn load_library(path: &str) -> Result<usize, SymbolError> {
+    let library = match std::fs::read_to_string(path) {
+        Ok(text) => parse_library(&text)?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => SymbolLibrary::default(),
+        Err(err) => return Err(SymbolError::Io(err)),
+    };
+    let count = library.symbols.len();
+    LIBRARY
+        .set(library)
+        .map_err(|_| SymbolError::AlreadyLoaded)?;
+    Ok(count)
+}
+
+fn parse_library(text: &str) -> Result<SymbolLibrary, SymbolError> {
+    let file: LibraryFile = ron::from_str(text).map_err(SymbolError::Parse)?;
+    Ok(SymbolLibrary {
+        symbols: file.symbols.into_iter().map(Symbol::from).collect(),
+    })
+}
+
+/// The loaded library, empty if none was loaded yet.
+pub fn library() -> &'static SymbolLibrary {
+    static EMPTY: SymbolLibrary = SymbolLibrary {
+        symbols: Vec::new(),
+    };
+    LIBRARY.get().unwrap_or(&EMPTY)
+}
+
+/// Index of a symbol of the library. Saved by the name of the symbol, so projects keep working
+/// when the library changes order. Only made by [`SymbolId::all`] and [`SymbolId::from_name`],
+/// so it is always in the library.
+#[derive(Debug, PartialEq, Hash, PartialOrd, Ord, Eq, Copy, Clone)]
+pub struct SymbolId(u16);
+
+impl SymbolId {
+    pub fn symbol(&self) -> &'static Symbol {
+        &library().symbols[self.0 as usize]
+    }
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        (0..library().symbols.len()).map(|i| Self(i as u16))
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().find(|id| id.symbol().name == name)
+    }
+}
+
+impl Serialize for SymbolId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.symbol().name)
+    }
+}
+
+impl<'de> Deserialize<'de> for SymbolId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Self::from_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown symbol {name}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structs::TikzComponent;
+
+    fn app_library_path() -> String {
+        format!("{}/{DEFAULT_LIBRARY}", env!("CARGO_MANIFEST_DIR"))
+    }
+
+    /// Loads the library of the app, whichever test asks first.
+    fn load_app_library() {
+        match load_library(&app_library_path()) {
+            Ok(_) | Err(SymbolError::AlreadyLoaded) => {}
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    #[test]
+    fn default_library() {
+        let text = std::fs::read_to_string(app_library_path()).unwrap();
+        let library = parse_library(&text).unwrap();
+        assert!(!library.symbols.is_empty());
+        for symbol in &library.symbols {
+            let mut pins: Vec<_> = symbol.pins.iter().map(|(pin, _)| pin).collect();
+            pins.sort();
+            pins.dedup();
+            assert_eq!(pins.len(), symbol.pins.len(), "{}", symbol.name);
+            assert!(!symbol.geometry.is_empty(), "{}", symbol.name);
+        }
+        let mut names: Vec<_> = library.symbols.iter().map(|symbol| &symbol.name).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), library.symbols.len());
+    }
+
+    #[test]
+    fn saved_by_name() {
+        load_app_library();
+        for id in SymbolId::all() {
+            let text = ron::to_string(&id).unwrap();
+            assert_eq!(text, format!("{:?}", id.symbol().name));
+            assert_eq!(ron::from_str::<SymbolId>(&text).unwrap(), id);
+        }
+        let buffer = SymbolId::from_name("Buffer").unwrap();
+        assert_eq!(buffer.symbol().tikz, "buffer port");
+    }
+
+    #[test]
+    fn unknown_symbols_are_refused() {
+        let err = ron::from_str::<SymbolId>("\"Nope\"").unwrap_err();
+        assert!(err.to_string().contains("unknown symbol Nope"), "{err}");
+        // The id never exists, so nothing can index the library with it.
+        assert!(ron::from_str::<TikzComponent>("Custom(\"Nope\")").is_err());
+        assert!(matches!(parse_library("(symbols: [(name: \"X\")])"), Err(SymbolError::Parse(_))));
+    }
+}
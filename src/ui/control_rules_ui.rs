@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+
+use super::helper::*;
+use crate::actions::select_node::Selected;
+use crate::graph::rules;
+use crate::project::{entity_records, RecordQuery};
+
+/// Column where the results of the last check are listed.
+#[derive(Component)]
+pub struct RuleCheckList;
+
+/// Component a result is about, selected when the result is clicked.
+#[derive(Component)]
+pub struct IssueTarget(Entity);
+
+/// Checks the electrical rules of the circuit and lists what was found in the panel.
+#[derive(Event)]
+pub struct CheckRules;
+
+pub fn check_rules(
+    _: Trigger<CheckRules>, mut commands: Commands, components: RecordQuery,
+    list: Single<Entity, With<RuleCheckList>>,
+) {
+    let (entities, records): (Vec<_>, Vec<_>) = entity_records(&components).into_iter().unzip();
+    let issues = rules::check(&records);
+
+    commands.entity(*list).despawn_descendants();
+    commands.entity(*list).with_children(|p| {
+        if issues.is_empty() {
+            draw_text(p, "Nenhum problema encontrado");
+        }
+        for issue in &issues {
+            let mut result = p.spawn((
+                Button,
+                Node {
+                    padding: UiRect::all(Val::Px(4.)),
+                    ..default()
+                },
+                BackgroundColor(spat_color(0.15)),
+            ));
+            result.with_child((Text::new(issue.message(&records)), TextFont::from_font_size(10.)));
+            if let Some(index) = issue.index {
+                result
+                    .insert(IssueTarget(entities[index]))
+                    .observe(super::handle_over_button)
+                    .observe(super::handle_out_button)
+                    .observe(select_issue_target);
+            }
+        }
+    });
+}
+
+fn select_issue_target(
+    trigger: Trigger<Pointer<Click>>, mut commands: Commands, targets: Query<&IssueTarget>,
+    selected: Query<Entity, With<Selected>>, components: Query<(), With<crate::TikzComponent>>,
+) {
+    let Ok(IssueTarget(target)) = targets.get(trigger.entity()) else {
+        return;
+    };
+    // The component may have been deleted after the check.
+    if !components.contains(*target) {
+        return;
+    }
+    for entity in &selected {
+        commands.entity(entity).remove::<Selected>();
+    }
+    commands.entity(*target).insert(Selected);
+}
@@ -13,58 +13,226 @@ pub struct LabelIdentifier;
 #[derive(Component)]
 pub struct ScaleIdentifier;
 
+#[derive(Component)]
+pub struct AnnotationIdentifier;
+
+#[derive(Component)]
+pub struct ValueIdentifier;
+
+/// Input of the nodes and frequencies of a Bode plot.
+#[derive(Component)]
+pub struct BodeIdentifier;
+
+/// Input of the Boolean expression to draw as gates.
+#[derive(Component)]
+pub struct ExpressionIdentifier;
+
+/// Input of the caption of the exported figure.
+#[derive(Component)]
+pub struct CaptionIdentifier;
+
+/// Input of the `\label` of the exported figure.
+#[derive(Component)]
+pub struct FigureLabelIdentifier;
+
+/// Background of an input whose text could not be read.
+const INVALID_COLOR: Color = Color::srgb(0.4, 0.1, 0.1);
+
+/// Text inputs of the selected panel, with the field each one edits.
+type FieldInputs<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut TextInputValue,
+        Has<LabelIdentifier>,
+        Has<ScaleIdentifier>,
+        Has<AnnotationIdentifier>,
+        Has<ValueIdentifier>,
+    ),
+>;
+
+fn fill_selected_ui(
+    selected: &[(&crate::TikzComponent, &crate::structs::Info)], selected_ui: &mut Visibility,
+    component_identifier: &mut Text, inputs: &mut FieldInputs,
+) {
+    let Some(&(cc, info)) = selected.first() else {
+        *selected_ui = Visibility::Hidden;
+        return;
+    };
+
+    // Fields are only filled when every selected component has the same value.
+    let common = |field: &dyn Fn(&crate::structs::Info) -> String| {
+        if selected.iter().all(|(_, other)| field(other) == field(info)) {
+            field(info)
+        } else {
+            String::new()
+        }
+    };
+
+    component_identifier.0 = match selected.len() {
+        1 => cc.to_string(),
+        n => format!("{n} componentes"),
+    };
+    let value = match cc.quantity() {
+        Some(quantity) if selected.iter().all(|(other, _)| other.quantity() == Some(quantity)) => {
+            common(&|info| {
+                info.value
+                    .map(|value| crate::structs::format_value(value, quantity))
+                    .unwrap_or_default()
+            })
+        }
+        _ => String::new(),
+    };
+    for (mut input, is_label, is_scale, is_annotation, is_value) in inputs.iter_mut() {
+        input.0 = match (is_label, is_scale, is_annotation, is_value) {
+            (true, ..) => common(&|info| info.label.clone()),
+            (_, true, ..) => common(&|info| info.scale.clone()),
+            (_, _, true, _) => common(&|info| info.annotations_text()),
+            (.., true) => value.clone(),
+            _ => continue,
+        };
+    }
+
+    *selected_ui = Visibility::Inherited;
+}
+
 pub fn enable_selected_ui(
     _: Trigger<OnAdd, crate::actions::select_node::Selected>,
     mut selected_ui: Single<&mut Visibility, With<SelectedInterface>>,
-    mut component_identifier: Single<
-        &mut Text,
-        (With<SelectedComponentIdentifier>, Without<LabelIdentifier>, Without<ScaleIdentifier>),
-    >,
-    mut label_identifier: Single<
-        &mut TextInputValue,
-        (With<LabelIdentifier>, Without<SelectedComponentIdentifier>, Without<ScaleIdentifier>),
-    >,
-    mut scale_identifier: Single<
-        &mut TextInputValue,
-        (With<ScaleIdentifier>, Without<SelectedComponentIdentifier>, Without<LabelIdentifier>),
-    >,
-    selected: Single<
+    mut component_identifier: Single<&mut Text, With<SelectedComponentIdentifier>>,
+    mut inputs: FieldInputs,
+    selected: Query<
         (&crate::TikzComponent, &crate::structs::Info),
         With<crate::actions::select_node::Selected>,
     >,
 ) {
-    let (cc, info) = *selected;
-
-    component_identifier.0 = cc.to_string();
-    label_identifier.0 = info.label.clone();
-    scale_identifier.0 = info.scale.clone();
-
-    **selected_ui = Visibility::Inherited;
+    fill_selected_ui(
+        &selected.iter().collect::<Vec<_>>(),
+        &mut selected_ui,
+        &mut component_identifier,
+        &mut inputs,
+    );
 }
 
 pub fn disable_selected_ui(
-    _: Trigger<OnRemove, crate::actions::select_node::Selected>,
-    selected_interface: Single<&mut Visibility, With<SelectedInterface>>,
+    trigger: Trigger<OnRemove, crate::actions::select_node::Selected>,
+    mut selected_ui: Single<&mut Visibility, With<SelectedInterface>>,
+    mut component_identifier: Single<&mut Text, With<SelectedComponentIdentifier>>,
+    mut inputs: FieldInputs,
+    selected: Query<
+        (Entity, &crate::TikzComponent, &crate::structs::Info),
+        With<crate::actions::select_node::Selected>,
+    >,
 ) {
-    let mut visibility = selected_interface.into_inner();
-    *visibility = Visibility::Hidden;
+    // The component is only removed after this observer runs.
+    let remaining: Vec<_> = selected
+        .iter()
+        .filter(|(entity, ..)| *entity != trigger.entity())
+        .map(|(_, cc, info)| (cc, info))
+        .collect();
+    fill_selected_ui(
+        &remaining,
+        &mut selected_ui,
+        &mut component_identifier,
+        &mut inputs,
+    );
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn submit_event(
     trigger: Trigger<TextInputSubmitEvent>, mut commands: Commands,
     mut focused: ResMut<super::FocusedInputText>, is_label: Query<&LabelIdentifier>,
-    mut selected: Single<
-        (Entity, &mut crate::structs::Info),
+    is_scale: Query<&ScaleIdentifier>, is_annotation: Query<&AnnotationIdentifier>,
+    is_value: Query<&ValueIdentifier>,
+    mut selected: Query<
+        (Entity, &crate::TikzComponent, &mut crate::structs::Info),
         With<crate::actions::select_node::Selected>,
     >,
 ) {
     let new_value = trigger.event();
     let entity = trigger.entity();
-
-    let info = match is_label.get(entity) {
-        Ok(_) => selected.1.with_label(new_value.value.clone()),
-        Err(_) => selected.1.with_scale(new_value.value.clone()),
-    };
-    commands.entity(selected.0).insert(info);
     *focused = super::FocusedInputText(Entity::PLACEHOLDER);
+    if !(is_label.contains(entity)
+        || is_scale.contains(entity)
+        || is_annotation.contains(entity)
+        || is_value.contains(entity))
+    {
+        return;
+    }
+
+    // Values are checked for every selected component before any of them changes.
+    let mut values = Vec::new();
+    if is_value.contains(entity) {
+        for (selected_entity, cc, _) in &selected {
+            let Some(quantity) = cc.quantity() else {
+                continue;
+            };
+            match crate::structs::parse_value(&new_value.value, quantity) {
+                Ok(value) => values.push((selected_entity, Some(value))),
+                Err(crate::structs::ValueError::Empty) => values.push((selected_entity, None)),
+                Err(err) => {
+                    warn!("{err}");
+                    commands.entity(entity).insert(BackgroundColor(INVALID_COLOR));
+                    return;
+                }
+            }
+        }
+        commands
+            .entity(entity)
+            .insert(BackgroundColor(super::spat_color(0.1)));
+    }
+
+    if !selected.is_empty() {
+        commands.trigger(crate::history::Checkpoint);
+    }
+    for (selected_entity, _, mut info) in selected.iter_mut() {
+        let info = if is_label.contains(entity) {
+            info.with_label(new_value.value.clone())
+        } else if is_annotation.contains(entity) {
+            info.with_annotations(crate::structs::Annotation::parse_list(&new_value.value))
+        } else if is_value.contains(entity) {
+            let Some(&(_, value)) = values.iter().find(|(e, _)| *e == selected_entity) else {
+                continue;
+            };
+            info.with_value(value)
+        } else {
+            info.with_scale(new_value.value.clone())
+        };
+        commands.entity(selected_entity).insert(info);
+    }
+}
+
+pub fn submit_bode(
+    trigger: Trigger<TextInputSubmitEvent>, mut commands: Commands,
+    is_bode: Query<&BodeIdentifier>,
+) {
+    if is_bode.contains(trigger.entity()) {
+        commands.trigger(crate::simulation::ExportBode {
+            text: trigger.event().value.clone(),
+        });
+    }
+}
+
+pub fn submit_expression(
+    trigger: Trigger<TextInputSubmitEvent>, mut commands: Commands,
+    is_expression: Query<&ExpressionIdentifier>,
+) {
+    if is_expression.contains(trigger.entity()) {
+        commands.trigger(crate::import::ImportExpression {
+            text: trigger.event().value.clone(),
+        });
+    }
+}
+
+pub fn submit_figure(
+    trigger: Trigger<TextInputSubmitEvent>,
+    mut profile: ResMut<crate::export::latex::ExportProfile>, is_caption: Query<&CaptionIdentifier>,
+    is_label: Query<&FigureLabelIdentifier>,
+) {
+    let value = trigger.event().value.trim().to_string();
+    if is_caption.contains(trigger.entity()) {
+        profile.caption = value;
+    } else if is_label.contains(trigger.entity()) {
+        profile.label = value;
+    }
 }
@@ -200,6 +200,40 @@ pub fn change_mouse_mode(p: &mut ChildBuilder) {
         .observe(super::handle_out_button)
         .observe(super::handle_click_create_button)
         .with_child((Text::new("C"), TextFont::from_font_size(10.)));
+
+        p.spawn((
+            Button,
+            Node {
+                width: Val::Px(12.),
+                height: Val::Px(12.),
+                justify_content: JustifyContent::Center,
+                border: UiRect::all(Val::Px(1.)),
+                ..default()
+            },
+            BorderColor(spat_color(0.1)),
+            BackgroundColor(spat_color(0.2)),
+        ))
+        .observe(super::handle_over_button)
+        .observe(super::handle_out_button)
+        .observe(super::handle_click_simulate_button)
+        .with_child((Text::new("L"), TextFont::from_font_size(10.)));
+
+        p.spawn((
+            Button,
+            Node {
+                width: Val::Px(12.),
+                height: Val::Px(12.),
+                justify_content: JustifyContent::Center,
+                border: UiRect::all(Val::Px(1.)),
+                ..default()
+            },
+            BorderColor(spat_color(0.1)),
+            BackgroundColor(spat_color(0.2)),
+        ))
+        .observe(super::handle_over_button)
+        .observe(super::handle_out_button)
+        .observe(super::handle_click_route_button)
+        .with_child((Text::new("R"), TextFont::from_font_size(10.)));
     });
 }
 
@@ -1,14 +1,22 @@
 use bevy::prelude::*;
 
 use crate::actions;
+use crate::export;
+use crate::history;
+use crate::import;
 use crate::input;
+use crate::project;
+use crate::simulation;
 use crate::structs;
+use crate::symbols;
 
 mod circuit_text;
+mod control_rules_ui;
 mod control_select_ui;
 mod helper;
 
 use circuit_text::*;
+use control_rules_ui::*;
 use control_select_ui::*;
 use helper::*;
 
@@ -20,6 +28,18 @@ pub struct PositionIdentifier;
 #[derive(Resource, Debug)]
 pub struct FocusedInputText(pub Entity);
 
+/// Text of the button that switches how values are exported.
+#[derive(Component)]
+struct ValueStyleText;
+
+/// Text of the button that switches what the exported circuit is wrapped in.
+#[derive(Component)]
+struct WrapperText;
+
+/// Text of the button that shows or hides the DC overlay.
+#[derive(Component)]
+struct DcOverlayText;
+
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
@@ -31,12 +51,20 @@ impl Plugin for UiPlugin {
                 (
                     update_radio.run_if(resource_changed::<structs::TikzComponent>),
                     focus_right_input.run_if(resource_changed::<FocusedInputText>),
+                    update_value_style_text.run_if(resource_changed::<structs::ValueStyle>),
+                    update_export_profile_ui
+                        .run_if(resource_changed::<export::latex::ExportProfile>),
+                    update_dc_overlay_text.run_if(resource_changed::<simulation::DcOverlay>),
                 ),
             )
             .add_observer(submit_event)
             .add_observer(update_circuit_text)
             .add_observer(enable_selected_ui)
-            .add_observer(disable_selected_ui);
+            .add_observer(disable_selected_ui)
+            .add_observer(check_rules)
+            .add_observer(submit_bode)
+            .add_observer(submit_expression)
+            .add_observer(submit_figure);
     }
 }
 
@@ -54,6 +82,9 @@ pub fn ui(mut commands: Commands) {
         })
         .observe(handle_click_on_grid)
         .observe(handle_drag_on_grid)
+        .observe(handle_drag_start_on_grid)
+        .observe(handle_box_drag_on_grid)
+        .observe(handle_drag_end_on_grid)
         .with_children(|p| {
             // Left panel
             p.spawn((
@@ -94,6 +125,29 @@ pub fn ui(mut commands: Commands) {
                         radio_button(p, Transistor, Transistor.to_string());
                         radio_button(p, Diode, Diode.to_string());
                         radio_button(p, Transformer, Transformer.to_string());
+                        radio_button(p, NandGate, NandGate.to_string());
+                        radio_button(p, NorGate, NorGate.to_string());
+                        radio_button(p, XnorGate, XnorGate.to_string());
+                        radio_button(p, Pnp, Pnp.to_string());
+                        radio_button(p, Nmos, Nmos.to_string());
+                        radio_button(p, Pmos, Pmos.to_string());
+                        radio_button(p, Zener, Zener.to_string());
+                        radio_button(p, Led, Led.to_string());
+                        radio_button(p, Battery, Battery.to_string());
+                        radio_button(p, SinVSource, SinVSource.to_string());
+                        radio_button(p, SinISource, SinISource.to_string());
+                        radio_button(p, ControlledVSource, ControlledVSource.to_string());
+                        radio_button(p, ControlledISource, ControlledISource.to_string());
+                        radio_button(p, Switch, Switch.to_string());
+                        radio_button(p, ClosedSwitch, ClosedSwitch.to_string());
+                        radio_button(p, Lamp, Lamp.to_string());
+                        radio_button(p, Fuse, Fuse.to_string());
+                        radio_button(p, Ammeter, Ammeter.to_string());
+                        radio_button(p, Voltmeter, Voltmeter.to_string());
+                        radio_button(p, VariableResistor, VariableResistor.to_string());
+                        for cc in symbols::SymbolId::all().map(Custom) {
+                            radio_button(p, cc, cc.to_string());
+                        }
                     });
 
                     separator(p);
@@ -117,8 +171,308 @@ pub fn ui(mut commands: Commands) {
                         .observe(handle_out_button)
                         .observe(handle_click_copy_button)
                         .with_child((Text::new("Copiar"), TextFont::from_font_size(12.)));
+
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_save_button)
+                        .with_child((Text::new("Salvar"), TextFont::from_font_size(12.)));
+
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_open_button)
+                        .with_child((Text::new("Abrir"), TextFont::from_font_size(12.)));
+
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_import_button)
+                        .with_child((Text::new("Importar"), TextFont::from_font_size(12.)));
+                    });
+
+                    create_row(p).with_children(|p| {
+                        draw_text_with_size(p, "DC:", 12.);
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_dc_overlay_button)
+                        .with_child((
+                            Text::new(dc_overlay_text(false)),
+                            TextFont::from_font_size(12.),
+                            DcOverlayText,
+                        ));
+
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_annotate_dc_button)
+                        .with_child((Text::new("Anotar v/i"), TextFont::from_font_size(12.)));
+                    });
+
+                    text_input(p, "Bode: entrada, saída, 10, 100k")
+                        .insert(BodeIdentifier)
+                        .observe(on_selected_text_input);
+
+                    create_row(p).with_children(|p| {
+                        draw_text_with_size(p, "Lógica:", 12.);
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_truth_table_button)
+                        .with_child((Text::new("Tabela verdade"), TextFont::from_font_size(12.)));
+                    });
+
+                    create_row(p).with_children(|p| {
+                        draw_text_with_size(p, "Minimizar:", 12.);
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_minimize_button)
+                        .with_child((Text::new("Substituir"), TextFont::from_font_size(12.)));
+
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_minimize_beside_button)
+                        .with_child((Text::new("Ao lado"), TextFont::from_font_size(12.)));
+
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_karnaugh_button)
+                        .with_child((Text::new("Mapa K"), TextFont::from_font_size(12.)));
+                    });
+
+                    text_input(p, "Expressão: (A & !B) | C")
+                        .insert(ExpressionIdentifier)
+                        .observe(on_selected_text_input);
+
+                    create_row(p).with_children(|p| {
+                        draw_text_with_size(p, "SPICE:", 12.);
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_spice_button)
+                        .with_child((Text::new("Exportar"), TextFont::from_font_size(12.)));
+
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_spice_import_button)
+                        .with_child((Text::new("Importar"), TextFont::from_font_size(12.)));
+                    });
+
+                    create_row(p).with_children(|p| {
+                        draw_text_with_size(p, "SVG:", 12.);
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_svg_button)
+                        .with_child((Text::new("Exportar"), TextFont::from_font_size(12.)));
+                    });
+
+                    create_row(p).with_children(|p| {
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_check_button)
+                        .with_child((Text::new("Verificar"), TextFont::from_font_size(12.)));
+                    });
+
+                    p.spawn((
+                        Node {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(2.),
+                            ..default()
+                        },
+                        RuleCheckList,
+                    ));
+
+                    create_row(p).with_children(|p| {
+                        draw_text_with_size(p, "Valores:", 12.);
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_value_style_button)
+                        .with_child((
+                            Text::new(value_style_text(structs::ValueStyle::default())),
+                            TextFont::from_font_size(12.),
+                            ValueStyleText,
+                        ));
                     });
 
+                    create_row(p).with_children(|p| {
+                        draw_text_with_size(p, "LaTeX:", 12.);
+                        p.spawn((
+                            Button,
+                            Node {
+                                padding: UiRect::all(Val::Px(7.)),
+                                border: UiRect::all(Val::Px(3.)),
+                                ..default()
+                            },
+                            BorderColor(spat_color(0.1)),
+                            BackgroundColor(spat_color(0.2)),
+                            BorderRadius::MAX,
+                        ))
+                        .observe(handle_over_button)
+                        .observe(handle_out_button)
+                        .observe(handle_click_wrapper_button)
+                        .with_child((
+                            Text::new(wrapper_text(export::latex::Wrapper::default())),
+                            TextFont::from_font_size(12.),
+                            WrapperText,
+                        ));
+                    });
+
+                    text_input(p, "Legenda da figura")
+                        .insert(CaptionIdentifier)
+                        .observe(on_selected_text_input);
+
+                    text_input(p, "Rótulo: fig:circuito")
+                        .insert(FigureLabelIdentifier)
+                        .observe(on_selected_text_input);
+
                     separator(p);
 
                     // Other configuration of components Label/Scale.
@@ -144,6 +498,12 @@ pub fn ui(mut commands: Commands) {
                         text_input(p, "Scale")
                             .insert(ScaleIdentifier)
                             .observe(on_selected_text_input);
+                        text_input(p, "Valor: 4k7, 10u, 2.2 MΩ")
+                            .insert(ValueIdentifier)
+                            .observe(on_selected_text_input);
+                        text_input(p, "v=, i=, f=, l_=, a=")
+                            .insert(AnnotationIdentifier)
+                            .observe(on_selected_text_input);
 
                         create_row(p).with_children(|p| {
                             p.spawn((
@@ -162,10 +522,60 @@ pub fn ui(mut commands: Commands) {
                             .observe(handle_click_delete_button)
                             .with_child((Text::new("Deletar"), TextFont::from_font_size(12.)));
                         });
+
+                        create_row(p).with_children(|p| {
+                            p.spawn((
+                                Button,
+                                Node {
+                                    padding: UiRect::all(Val::Px(7.)),
+                                    border: UiRect::all(Val::Px(3.)),
+                                    ..default()
+                                },
+                                BorderColor(spat_color(0.1)),
+                                BackgroundColor(spat_color(0.2)),
+                                BorderRadius::MAX,
+                            ))
+                            .observe(handle_over_button)
+                            .observe(handle_out_button)
+                            .observe(handle_click_rotate_button)
+                            .with_child((Text::new("Girar"), TextFont::from_font_size(12.)));
+
+                            p.spawn((
+                                Button,
+                                Node {
+                                    padding: UiRect::all(Val::Px(7.)),
+                                    border: UiRect::all(Val::Px(3.)),
+                                    ..default()
+                                },
+                                BorderColor(spat_color(0.1)),
+                                BackgroundColor(spat_color(0.2)),
+                                BorderRadius::MAX,
+                            ))
+                            .observe(handle_over_button)
+                            .observe(handle_out_button)
+                            .observe(handle_click_mirror_x_button)
+                            .with_child((Text::new("Espelhar H"), TextFont::from_font_size(12.)));
+
+                            p.spawn((
+                                Button,
+                                Node {
+                                    padding: UiRect::all(Val::Px(7.)),
+                                    border: UiRect::all(Val::Px(3.)),
+                                    ..default()
+                                },
+                                BorderColor(spat_color(0.1)),
+                                BackgroundColor(spat_color(0.2)),
+                                BorderRadius::MAX,
+                            ))
+                            .observe(handle_over_button)
+                            .observe(handle_out_button)
+                            .observe(handle_click_mirror_y_button)
+                            .with_child((Text::new("Espelhar V"), TextFont::from_font_size(12.)));
+                        });
                     });
                 });
 
-                // Buttons P S C
+                // Buttons P S C L
                 change_mouse_mode(p);
             });
 
@@ -202,12 +612,15 @@ pub fn ui(mut commands: Commands) {
         });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_click_on_grid(
     trigger: Trigger<Pointer<Click>>, mut commands: Commands,
     cursor_position: Res<structs::CursorPosition>,
     selectable: Query<(Entity, &GlobalTransform), With<actions::select_node::Selectable>>,
     selected: Query<(Entity, &GlobalTransform), With<actions::select_node::Selected>>,
+    selection_box: Query<&actions::select_node::SelectionBox>,
     mouse_mode: Res<State<input::MouseMode>>, mut focused: ResMut<FocusedInputText>,
+    keys: Res<ButtonInput<KeyCode>>,
 ) {
     if trigger.event().event.button != PointerButton::Primary {
         return;
@@ -218,24 +631,66 @@ fn handle_click_on_grid(
         return;
     }
 
+    // The click that ends a box selection is handled on the drag end.
+    if selection_box
+        .iter()
+        .any(|selection_box| selection_box.0 != cursor_position.pos)
+    {
+        return;
+    }
+
     *focused = FocusedInputText(Entity::PLACEHOLDER);
 
+    if *mouse_mode == input::MouseMode::Simulate {
+        commands.trigger(simulation::live::ToggleLogicInput {
+            pos: cursor_position.pos,
+        });
+        return;
+    }
+
+    if *mouse_mode == input::MouseMode::Route {
+        commands.trigger(actions::draw_components::RouteWire {
+            pos: cursor_position.pos,
+        });
+        return;
+    }
+
     if *mouse_mode == input::MouseMode::SelectAndCreate {
-        if let Ok((selected_entity, selected_transform)) = selected.get_single() {
+        let clicked = selectable
+            .iter()
+            .find(|(_, transform)| close_to(cursor_position.pos, transform.translation().truncate()))
+            .map(|(ent, _)| ent);
+
+        if actions::select_node::shift_pressed(&keys) {
+            match clicked {
+                Some(ent) if selected.contains(ent) => {
+                    commands
+                        .entity(ent)
+                        .remove::<actions::select_node::Selected>();
+                }
+                Some(ent) => {
+                    commands.entity(ent).insert(actions::select_node::Selected);
+                }
+                None => {}
+            }
+            return;
+        }
+
+        let mut was_selected = false;
+        for (selected_entity, selected_transform) in &selected {
             commands
                 .entity(selected_entity)
                 .remove::<actions::select_node::Selected>();
             let selected_pos = selected_transform.translation().truncate();
-            if close_to(cursor_position.pos, selected_pos) {
-                return;
-            }
+            was_selected |= close_to(cursor_position.pos, selected_pos);
         }
-        for (ent, transform) in &selectable {
-            let selected_pos = transform.translation().truncate();
-            if close_to(cursor_position.pos, selected_pos) {
-                commands.entity(ent).insert(actions::select_node::Selected);
-                return;
-            }
+        if was_selected {
+            return;
+        }
+
+        if let Some(ent) = clicked {
+            commands.entity(ent).insert(actions::select_node::Selected);
+            return;
         }
     }
 
@@ -244,6 +699,69 @@ fn handle_click_on_grid(
     })
 }
 
+fn handle_drag_start_on_grid(
+    trigger: Trigger<Pointer<DragStart>>, mut commands: Commands,
+    cursor_position: Res<structs::CursorPosition>, mouse_mode: Res<State<input::MouseMode>>,
+) {
+    if *mouse_mode != input::MouseMode::SelectAndCreate
+        || trigger.event().button != PointerButton::Primary
+    {
+        return;
+    }
+
+    commands.spawn((
+        Sprite {
+            color: Color::srgba(0.3, 0.5, 1.0, 0.2),
+            ..default()
+        },
+        Transform::from_translation(cursor_position.pos.extend(50.)),
+        actions::select_node::SelectionBox(cursor_position.pos),
+    ));
+}
+
+fn handle_box_drag_on_grid(
+    _: Trigger<Pointer<Drag>>, cursor_position: Res<structs::CursorPosition>,
+    mut selection_box: Query<(&actions::select_node::SelectionBox, &mut Transform)>,
+) {
+    for (selection_box, mut transform) in selection_box.iter_mut() {
+        let rect = selection_box.rect(cursor_position.pos);
+        transform.translation = rect.center().extend(50.);
+        transform.scale = rect.size().extend(1.);
+    }
+}
+
+fn handle_drag_end_on_grid(
+    _: Trigger<Pointer<DragEnd>>, mut commands: Commands,
+    cursor_position: Res<structs::CursorPosition>,
+    selection_box: Query<(Entity, &actions::select_node::SelectionBox)>,
+    selectable: Query<(Entity, &GlobalTransform), With<actions::select_node::Selectable>>,
+    selected: Query<Entity, With<actions::select_node::Selected>>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    let Ok((box_entity, selection_box)) = selection_box.get_single() else {
+        return;
+    };
+    commands.entity(box_entity).despawn();
+
+    let rect = selection_box.rect(cursor_position.pos);
+    if rect.is_empty() {
+        return;
+    }
+
+    // With shift the box adds to the selection, otherwise it replaces it.
+    let keep_outside = actions::select_node::shift_pressed(&keys);
+    for (ent, transform) in &selectable {
+        let inside = rect.contains(transform.translation().truncate());
+        if inside && !selected.contains(ent) {
+            commands.entity(ent).insert(actions::select_node::Selected);
+        } else if !inside && !keep_outside && selected.contains(ent) {
+            commands
+                .entity(ent)
+                .remove::<actions::select_node::Selected>();
+        }
+    }
+}
+
 fn handle_drag_on_grid(
     trigger: Trigger<Pointer<Drag>>, mut camera: Single<&mut Transform, With<Camera2d>>,
     mouse_mode: Res<State<input::MouseMode>>, time: Res<Time>,
@@ -291,9 +809,25 @@ fn handle_out_button(trigger: Trigger<Pointer<Out>>, mut background: Query<&mut
 
 fn handle_click_delete_button(
     _: Trigger<Pointer<Click>>, mut commands: Commands,
-    selected: Single<Entity, With<actions::select_node::Selected>>,
+    selected: Query<Entity, With<actions::select_node::Selected>>,
 ) {
-    commands.trigger_targets(actions::DeleteComponent, *selected);
+    if selected.is_empty() {
+        return;
+    }
+    commands.trigger(history::Checkpoint);
+    commands.trigger_targets(actions::DeleteComponent, selected.iter().collect::<Vec<_>>());
+}
+
+fn handle_click_rotate_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(actions::OrientSelected::Rotate);
+}
+
+fn handle_click_mirror_x_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(actions::OrientSelected::Mirror { vertical: false });
+}
+
+fn handle_click_mirror_y_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(actions::OrientSelected::Mirror { vertical: true });
 }
 
 fn handle_click_copy_button(_: Trigger<Pointer<Click>>, circuit: Single<&Text, With<CircuitText>>) {
@@ -301,6 +835,148 @@ fn handle_click_copy_button(_: Trigger<Pointer<Click>>, circuit: Single<&Text, W
     clipboard.set_text(circuit.0.clone()).unwrap();
 }
 
+fn handle_click_import_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    let text = arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text());
+    match text {
+        Ok(text) => commands.trigger(import::ImportTikz { text }),
+        Err(err) => error!("Could not read the clipboard: {err}"),
+    }
+}
+
+fn handle_click_check_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(CheckRules);
+}
+
+fn dc_overlay_text(enabled: bool) -> &'static str {
+    if enabled {
+        "Ocultar"
+    } else {
+        "Mostrar"
+    }
+}
+
+fn handle_click_dc_overlay_button(
+    _: Trigger<Pointer<Click>>, mut overlay: ResMut<simulation::DcOverlay>,
+) {
+    overlay.enabled = !overlay.enabled;
+}
+
+fn update_dc_overlay_text(
+    overlay: Res<simulation::DcOverlay>, mut text: Single<&mut Text, With<DcOverlayText>>,
+) {
+    text.0 = dc_overlay_text(overlay.enabled).to_string();
+}
+
+fn handle_click_annotate_dc_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(simulation::AnnotateDc);
+}
+
+fn handle_click_truth_table_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(simulation::ExportTruthTable);
+}
+
+fn handle_click_minimize_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(simulation::MinimizeLogic { beside: false });
+}
+
+fn handle_click_minimize_beside_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(simulation::MinimizeLogic { beside: true });
+}
+
+fn handle_click_karnaugh_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(simulation::ExportKarnaugh);
+}
+
+fn handle_click_spice_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(export::ExportSpice);
+}
+
+fn handle_click_svg_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(export::ExportSvg);
+}
+
+/// Opens the deck given on the command line, or else the one beside the project file, `.cir` or
+/// `.sp`.
+fn handle_click_spice_import_button(
+    _: Trigger<Pointer<Click>>, mut commands: Commands, deck: Res<import::SpiceFile>,
+    file: Res<project::ProjectFile>,
+) {
+    let paths = match &deck.0 {
+        Some(path) => vec![path.clone()],
+        None => ["cir", "sp"]
+            .map(|extension| export::sibling_path(&file, extension))
+            .to_vec(),
+    };
+    match paths.iter().find_map(|path| std::fs::read_to_string(path).ok()) {
+        Some(text) => commands.trigger(import::ImportSpice { text }),
+        None => error!("Could not read {}", paths.join(" or ")),
+    }
+}
+
+fn value_style_text(style: structs::ValueStyle) -> &'static str {
+    match style {
+        structs::ValueStyle::Siunitx => "siunitx",
+        structs::ValueStyle::Plain => "Texto",
+    }
+}
+
+fn handle_click_value_style_button(
+    _: Trigger<Pointer<Click>>, mut style: ResMut<structs::ValueStyle>,
+) {
+    *style = match *style {
+        structs::ValueStyle::Siunitx => structs::ValueStyle::Plain,
+        structs::ValueStyle::Plain => structs::ValueStyle::Siunitx,
+    };
+}
+
+fn update_value_style_text(
+    style: Res<structs::ValueStyle>, mut text: Single<&mut Text, With<ValueStyleText>>,
+) {
+    text.0 = value_style_text(*style).to_string();
+}
+
+fn wrapper_text(wrapper: export::latex::Wrapper) -> &'static str {
+    match wrapper {
+        export::latex::Wrapper::Bare => "\\draw",
+        export::latex::Wrapper::Environment => "circuitikz",
+        export::latex::Wrapper::Figure => "figure",
+        export::latex::Wrapper::Standalone => "standalone",
+    }
+}
+
+fn handle_click_wrapper_button(
+    _: Trigger<Pointer<Click>>, mut profile: ResMut<export::latex::ExportProfile>,
+) {
+    profile.wrapper = profile.wrapper.next();
+}
+
+/// Shows the wrapper of the profile and its figure caption and label, also after a project opens.
+fn update_export_profile_ui(
+    profile: Res<export::latex::ExportProfile>, mut text: Single<&mut Text, With<WrapperText>>,
+    mut inputs: Query<(
+        &mut crate::input_widget::TextInputValue,
+        Has<CaptionIdentifier>,
+        Has<FigureLabelIdentifier>,
+    )>,
+) {
+    text.0 = wrapper_text(profile.wrapper).to_string();
+    for (mut input, is_caption, is_label) in &mut inputs {
+        if is_caption {
+            input.0.clone_from(&profile.caption);
+        } else if is_label {
+            input.0.clone_from(&profile.label);
+        }
+    }
+}
+
+fn handle_click_save_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(project::SaveProject);
+}
+
+fn handle_click_open_button(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(project::LoadProject);
+}
+
 fn handle_click_pan_button(
     _: Trigger<Pointer<Click>>, mut next_state: ResMut<NextState<input::MouseMode>>,
 ) {
@@ -318,3 +994,15 @@ fn handle_click_create_button(
 ) {
     next_state.set(input::MouseMode::Create);
 }
+
+fn handle_click_simulate_button(
+    _: Trigger<Pointer<Click>>, mut next_state: ResMut<NextState<input::MouseMode>>,
+) {
+    next_state.set(input::MouseMode::Simulate);
+}
+
+fn handle_click_route_button(
+    _: Trigger<Pointer<Click>>, mut next_state: ResMut<NextState<input::MouseMode>>,
+) {
+    next_state.set(input::MouseMode::Route);
+}